@@ -0,0 +1,243 @@
+use super::{BDD, BDDNode};
+use std::collections::HashMap;
+
+/// BDD builder is an internal structure used to implement operations which need to combine
+/// intermediate results (quantification, composition, ...), which is not possible directly
+/// in the post-order vector of a [BDD].
+///
+/// All nodes live in one growing vector with a unique table, hence every node is reduced
+/// and stored only once. Nodes are always appended after their children, so the vector is
+/// topologically sorted, but it can also contain nodes which are no longer used. The result
+/// is obtained using [BDDBuilder::export], which only keeps the nodes reachable from the root.
+///
+/// Internal operations are recursive. This is safe, because every recursive call descends
+/// at least one variable lower, so the depth is bounded by the number of variables.
+pub(super) struct BDDBuilder {
+    num_vars: u32,
+    nodes: Vec<BDDNode>,
+    unique: HashMap<BDDNode, usize>
+}
+
+impl BDDBuilder {
+
+    /// Create a new builder with just the two terminal nodes.
+    pub fn new(num_vars: u32) -> BDDBuilder {
+        return BDDBuilder {
+            num_vars,
+            nodes: vec![BDDNode::mk_zero(num_vars), BDDNode::mk_one(num_vars)],
+            unique: HashMap::new()
+        }
+    }
+
+    /// Number of nodes (including terminals and unused nodes) in this builder.
+    pub fn size(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    pub fn var(&self, node: usize) -> u32 {
+        return self.nodes[node].var;
+    }
+
+    pub fn low(&self, node: usize) -> usize {
+        return self.nodes[node].low as usize;
+    }
+
+    pub fn high(&self, node: usize) -> usize {
+        return self.nodes[node].high as usize;
+    }
+
+    /// Create (or find) a reduced node with the given variable and children.
+    pub fn mk_node(&mut self, var: u32, low: usize, high: usize) -> usize {
+        if low == high { return low }
+        let node = BDDNode { var, low: low as u32, high: high as u32 };
+        if let Some(index) = self.unique.get(&node) {
+            return *index;
+        }
+        self.nodes.push(node);
+        self.unique.insert(node, self.nodes.len() - 1);
+        return self.nodes.len() - 1;
+    }
+
+    /// Copy all nodes of the given [bdd] into this builder and return the index of its root.
+    pub fn import(&mut self, bdd: &BDD) -> usize {
+        if bdd.size() == 1 { return 0 }
+        let mut new_index: Vec<usize> = vec![0, 1];
+        for i in 2..bdd.size() {
+            let node = bdd.0[i];
+            let index = self.mk_node(node.var, new_index[node.low as usize], new_index[node.high as usize]);
+            new_index.push(index);
+        }
+        return new_index[bdd.last_index()];
+    }
+
+    /// Copy all nodes of the given [bdd] into this builder, replacing every variable `v`
+    /// with `mapping[v]`, and return the index of its root. The mapping must be injective,
+    /// but it does not have to preserve the variable ordering: in such case, the affected
+    /// nodes are rebuilt using the expansion `(var & high) | (!var & low)`, using [and_cache]
+    /// and [or_cache] for the respective operations.
+    pub fn import_renamed(
+        &mut self,
+        bdd: &BDD,
+        mapping: &[u32],
+        and_cache: &mut HashMap<(usize, usize), usize>,
+        or_cache: &mut HashMap<(usize, usize), usize>
+    ) -> usize {
+        if bdd.size() == 1 { return 0 }
+        let mut new_index: Vec<usize> = vec![0, 1];
+        for i in 2..bdd.size() {
+            let var = mapping[bdd.var(i)];
+            let low = new_index[bdd.low_link(i)];
+            let high = new_index[bdd.high_link(i)];
+            let index = if var < self.var(low) && var < self.var(high) {
+                // ordering is preserved, the node can be created directly
+                self.mk_node(var, low, high)
+            } else {
+                let positive = self.mk_node(var, 0, 1);
+                let negative = self.mk_node(var, 1, 0);
+                let high = self.and(positive, high, and_cache);
+                let low = self.and(negative, low, and_cache);
+                self.or(low, high, or_cache)
+            };
+            new_index.push(index);
+        }
+        return new_index[bdd.last_index()];
+    }
+
+    /// Remove all nodes which are not reachable from the given [roots]. Returns the new
+    /// indices of the roots; all other node indices are invalidated.
+    pub fn compact(&mut self, roots: &[usize]) -> Vec<usize> {
+        let mut is_reachable = vec![false; self.nodes.len()];
+        for root in roots {
+            is_reachable[*root] = true;
+        }
+        // children are always stored before their parents, so one backward pass is enough
+        for node in (2..self.nodes.len()).rev() {
+            if is_reachable[node] {
+                is_reachable[self.low(node)] = true;
+                is_reachable[self.high(node)] = true;
+            }
+        }
+        let mut compacted = BDDBuilder::new(self.num_vars);
+        let mut new_index: Vec<usize> = vec![0, 1];
+        for node in 2..self.nodes.len() {
+            let index = if is_reachable[node] {
+                compacted.mk_node(self.var(node), new_index[self.low(node)], new_index[self.high(node)])
+            } else { 0 };
+            new_index.push(index);
+        }
+        *self = compacted;
+        return roots.iter().map(|root| new_index[*root]).collect();
+    }
+
+    /// Convert the sub-graph given by [root] into a standalone BDD. The nodes are ordered
+    /// in DFS post-order (visiting the high link first), which is the same order as produced
+    /// by [super::BDDWorker::apply], hence equal functions result in equal BDDs.
+    pub fn export(&self, root: usize) -> BDD {
+        if root == 0 { return BDD(vec![BDDNode::mk_zero(self.num_vars)]) }
+        let mut result: Vec<BDDNode> = vec![BDDNode::mk_zero(self.num_vars), BDDNode::mk_one(self.num_vars)];
+        let mut new_index: HashMap<usize, usize> = HashMap::new();
+        new_index.insert(0, 0);
+        new_index.insert(1, 1);
+        let mut stack: Vec<usize> = vec![root];
+        while let Some(node) = stack.last() {
+            let node = *node;
+            if new_index.contains_key(&node) {
+                stack.pop();
+                continue;
+            }
+            let (low, high) = (self.low(node), self.high(node));
+            match (new_index.get(&low), new_index.get(&high)) {
+                (Some(new_low), Some(new_high)) => {
+                    result.push(BDDNode { var: self.var(node), low: *new_low as u32, high: *new_high as u32 });
+                    new_index.insert(node, result.len() - 1);
+                    stack.pop();
+                }
+                (None, Some(_)) => stack.push(low),
+                (Some(_), None) => stack.push(high),
+                (None, None) => {
+                    stack.push(low);
+                    stack.push(high);
+                }
+            }
+        }
+        return BDD(result);
+    }
+
+    /// Generic binary operation over two nodes of this builder. The [terminal_lookup]
+    /// function returns the result directly when it can be determined without further
+    /// recursion (for example, `and` with `zero`).
+    pub fn apply<T>(&mut self, left: usize, right: usize, terminal_lookup: &T, cache: &mut HashMap<(usize, usize), usize>) -> usize
+        where T: Fn(usize, usize) -> Option<usize>
+    {
+        if let Some(result) = terminal_lookup(left, right) { return result }
+        if let Some(result) = cache.get(&(left, right)) { return *result }
+        let (var_left, var_right) = (self.var(left), self.var(right));
+        let var = std::cmp::min(var_left, var_right);
+        let (left_low, left_high) = if var_left == var { (self.low(left), self.high(left)) } else { (left, left) };
+        let (right_low, right_high) = if var_right == var { (self.low(right), self.high(right)) } else { (right, right) };
+        let low = self.apply(left_low, right_low, terminal_lookup, cache);
+        let high = self.apply(left_high, right_high, terminal_lookup, cache);
+        let result = self.mk_node(var, low, high);
+        cache.insert((left, right), result);
+        return result;
+    }
+
+    /// Conjunction of two nodes.
+    pub fn and(&mut self, left: usize, right: usize, cache: &mut HashMap<(usize, usize), usize>) -> usize {
+        return self.apply(left, right, &and_lookup, cache);
+    }
+
+    /// Disjunction of two nodes.
+    pub fn or(&mut self, left: usize, right: usize, cache: &mut HashMap<(usize, usize), usize>) -> usize {
+        return self.apply(left, right, &or_lookup, cache);
+    }
+
+    /// If-then-else operation `(cond & then) | (!cond & otherwise)` on three nodes.
+    pub fn ite(&mut self, cond: usize, then: usize, otherwise: usize, cache: &mut HashMap<(usize, usize, usize), usize>) -> usize {
+        if cond == 1 || then == otherwise { return then }
+        if cond == 0 { return otherwise }
+        if then == 1 && otherwise == 0 { return cond }
+        if let Some(result) = cache.get(&(cond, then, otherwise)) { return *result }
+        let var = std::cmp::min(self.var(cond), std::cmp::min(self.var(then), self.var(otherwise)));
+        let (cond_low, cond_high) = self.cofactors(cond, var);
+        let (then_low, then_high) = self.cofactors(then, var);
+        let (otherwise_low, otherwise_high) = self.cofactors(otherwise, var);
+        let low = self.ite(cond_low, then_low, otherwise_low, cache);
+        let high = self.ite(cond_high, then_high, otherwise_high, cache);
+        let result = self.mk_node(var, low, high);
+        cache.insert((cond, then, otherwise), result);
+        return result;
+    }
+
+    /// Low and high cofactor of the [node] with respect to [var], assuming the node
+    /// does not depend on variables smaller than [var].
+    fn cofactors(&self, node: usize, var: u32) -> (usize, usize) {
+        return if self.var(node) == var { (self.low(node), self.high(node)) } else { (node, node) };
+    }
+
+}
+
+/// Terminal cases of conjunction.
+pub(super) fn and_lookup(left: usize, right: usize) -> Option<usize> {
+    return if left == 0 || right == 0 { Some(0) }
+    else if left == 1 { Some(right) }
+    else if right == 1 || left == right { Some(left) }
+    else { None }
+}
+
+/// Terminal cases of exclusive disjunction.
+pub(super) fn xor_lookup(left: usize, right: usize) -> Option<usize> {
+    return if left == right { Some(0) }
+    else if left == 0 { Some(right) }
+    else if right == 0 { Some(left) }
+    else if left <= 1 && right <= 1 { Some(1) }
+    else { None }
+}
+
+/// Terminal cases of disjunction.
+pub(super) fn or_lookup(left: usize, right: usize) -> Option<usize> {
+    return if left == 1 || right == 1 { Some(1) }
+    else if left == 0 { Some(right) }
+    else if right == 0 || left == right { Some(left) }
+    else { None }
+}
@@ -0,0 +1,253 @@
+use super::{BDD, BDDWorker};
+
+/// Model counting and enumeration of satisfying valuations. Variables which do not appear
+/// in the BDD are "don't care" variables: they are counted as both true and false, since
+/// a BDD always ranges over all [BDD::num_vars] variables of its worker.
+impl BDDWorker {
+
+    /// Number of satisfying valuations of the given [bdd] as a floating point number.
+    /// For large numbers of variables, the result is only approximate.
+    pub fn cardinality(&self, bdd: &BDD) -> f64 {
+        if bdd.size() == 1 { return 0.0 }
+        let mut count: Vec<f64> = vec![0.0, 1.0];
+        for i in 2..bdd.size() {
+            let (low, high) = (bdd.low_link(i), bdd.high_link(i));
+            let low_count = count[low] * 2.0f64.powi((bdd.var(low) - bdd.var(i) - 1) as i32);
+            let high_count = count[high] * 2.0f64.powi((bdd.var(high) - bdd.var(i) - 1) as i32);
+            count.push(low_count + high_count);
+        }
+        let root = bdd.last_index();
+        return count[root] * 2.0f64.powi(bdd.var(root) as i32);
+    }
+
+    /// Exact number of satisfying valuations of the given [bdd]. Panics if the number
+    /// does not fit into u128 (this can only happen with 128 or more variables).
+    pub fn exact_cardinality(&self, bdd: &BDD) -> u128 {
+        if bdd.size() == 1 { return 0 }
+        let mut count: Vec<u128> = vec![0, 1];
+        for i in 2..bdd.size() {
+            let (low, high) = (bdd.low_link(i), bdd.high_link(i));
+            let low_count = shift_count(count[low], bdd.var(low) - bdd.var(i) - 1);
+            let high_count = shift_count(count[high], bdd.var(high) - bdd.var(i) - 1);
+            count.push(low_count.checked_add(high_count).unwrap_or_else(|| cardinality_overflow()));
+        }
+        let root = bdd.last_index();
+        return shift_count(count[root], bdd.var(root));
+    }
+
+    /// Find one satisfying valuation of the given [bdd], or [None] if the BDD is not
+    /// satisfiable. Don't care variables are set to false.
+    pub fn sat_witness(&self, bdd: &BDD) -> Option<Vec<bool>> {
+        return SatCubes::new(bdd).next().map(|cube| {
+            cube.iter().map(|value| value.unwrap_or(false)).collect()
+        });
+    }
+
+    /// Iterate over all satisfying valuations of the given [bdd]. Every valuation assigns
+    /// a value to all variables, hence there is exactly [BDDWorker::exact_cardinality] of them.
+    pub fn sat_valuations<'a>(&self, bdd: &'a BDD) -> SatValuations<'a> {
+        return SatValuations { cubes: SatCubes::new(bdd), current: None }
+    }
+
+    /// Iterate over all satisfying cubes of the given [bdd], i.e. paths leading to the
+    /// `one` node. Variables not fixed by a path are [None]. The cubes are disjoint.
+    pub fn sat_cubes<'a>(&self, bdd: &'a BDD) -> SatCubes<'a> {
+        return SatCubes::new(bdd);
+    }
+
+}
+
+/// Multiply [count] by `2^shift`, panicking on overflow.
+fn shift_count(count: u128, shift: usize) -> u128 {
+    if count == 0 { return 0 }
+    if shift >= 128 || count.leading_zeros() < shift as u32 { cardinality_overflow() }
+    return count << shift;
+}
+
+fn cardinality_overflow() -> ! {
+    panic!("BDD cardinality does not fit into u128.");
+}
+
+/// Iterator over satisfying cubes (paths to `one`) of a BDD, see [BDDWorker::sat_cubes].
+pub struct SatCubes<'a> {
+    bdd: &'a BDD,
+    // Nodes on the current path together with the chosen branch (false = low, true = high).
+    path: Vec<(usize, bool)>,
+    started: bool
+}
+
+impl SatCubes<'_> {
+
+    fn new(bdd: &BDD) -> SatCubes<'_> {
+        return SatCubes { bdd, path: Vec::new(), started: false }
+    }
+
+    /// Extend the path from [node] to the `one` node, preferring low links. Since the BDD
+    /// is reduced, every non-terminal node has a path to `one` and only one of its links
+    /// can lead to `zero`.
+    fn descend(&mut self, mut node: usize) {
+        while node > 1 {
+            let low = self.bdd.low_link(node);
+            if low != 0 {
+                self.path.push((node, false));
+                node = low;
+            } else {
+                self.path.push((node, true));
+                node = self.bdd.high_link(node);
+            }
+        }
+    }
+
+    fn current_cube(&self) -> Vec<Option<bool>> {
+        let mut cube = vec![None; self.bdd.num_vars() as usize];
+        for (node, value) in &self.path {
+            cube[self.bdd.var(*node)] = Some(*value);
+        }
+        return cube;
+    }
+
+}
+
+impl Iterator for SatCubes<'_> {
+    type Item = Vec<Option<bool>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.bdd.size() == 1 { return None }
+            self.descend(self.bdd.last_index());
+            return Some(self.current_cube());
+        }
+        // backtrack to the deepest node where the high branch is still unexplored
+        while let Some((node, value)) = self.path.pop() {
+            let high = self.bdd.high_link(node);
+            if !value && high != 0 {
+                self.path.push((node, true));
+                self.descend(high);
+                return Some(self.current_cube());
+            }
+        }
+        return None;
+    }
+}
+
+/// Iterator over satisfying valuations of a BDD, see [BDDWorker::sat_valuations].
+pub struct SatValuations<'a> {
+    cubes: SatCubes<'a>,
+    // current cube and the last valuation returned from it
+    current: Option<(Vec<Option<bool>>, Vec<bool>)>
+}
+
+impl Iterator for SatValuations<'_> {
+    type Item = Vec<bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((cube, valuation)) = &mut self.current {
+            // increment the valuation as a binary number over the don't care variables
+            for var in 0..cube.len() {
+                if cube[var].is_none() {
+                    if valuation[var] {
+                        valuation[var] = false;
+                    } else {
+                        valuation[var] = true;
+                        return Some(valuation.clone());
+                    }
+                }
+            }
+        }
+        // all valuations of the current cube are done, continue with the next one
+        return match self.cubes.next() {
+            None => {
+                self.current = None;
+                None
+            }
+            Some(cube) => {
+                let valuation: Vec<bool> = cube.iter().map(|value| value.unwrap_or(false)).collect();
+                self.current = Some((cube, valuation.clone()));
+                Some(valuation)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdd::tests::mk_test_bdd;
+
+    // Check whether the valuation satisfies the BDD by restricting all variables.
+    fn is_satisfied(worker: &BDDWorker, bdd: &BDD, valuation: &[bool]) -> bool {
+        let cube: Vec<(u32, bool)> = valuation.iter().enumerate().map(|(v, b)| (v as u32, *b)).collect();
+        return worker.is_true(&worker.restrict_cube(bdd, &cube));
+    }
+
+    #[test]
+    fn bdd_cardinality() {
+        let worker = BDDWorker::new_anonymous(4);
+        assert_eq!(0, worker.exact_cardinality(&worker.mk_false()));
+        assert_eq!(16, worker.exact_cardinality(&worker.mk_true()));
+        assert_eq!(8, worker.exact_cardinality(&worker.mk_var(3)));
+        assert_eq!(8, worker.exact_cardinality(&mk_test_bdd(&worker)));
+        assert_eq!(8.0, worker.cardinality(&mk_test_bdd(&worker)));
+        assert_eq!(16.0, worker.cardinality(&worker.mk_true()));
+        assert_eq!(0.0, worker.cardinality(&worker.mk_false()));
+        let conjunction = worker.mk_and(&worker.mk_var(0), &worker.mk_not(&worker.mk_var(3)));
+        assert_eq!(4, worker.exact_cardinality(&conjunction));
+    }
+
+    #[test]
+    fn bdd_cardinality_many_variables() {
+        let worker = BDDWorker::new_anonymous(127);
+        assert_eq!(1u128 << 127, worker.exact_cardinality(&worker.mk_true()));
+        assert_eq!(1u128 << 126, worker.exact_cardinality(&worker.mk_var(100)));
+        assert_eq!(2.0f64.powi(126), worker.cardinality(&worker.mk_var(100)));
+        let worker = BDDWorker::new_anonymous(300);
+        assert_eq!(2.0f64.powi(299), worker.cardinality(&worker.mk_var(0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_exact_cardinality_overflow() {
+        let worker = BDDWorker::new_anonymous(128);
+        worker.exact_cardinality(&worker.mk_true());
+    }
+
+    #[test]
+    fn bdd_sat_witness() {
+        let worker = BDDWorker::new_anonymous(4);
+        assert_eq!(None, worker.sat_witness(&worker.mk_false()));
+        assert_eq!(Some(vec![false; 4]), worker.sat_witness(&worker.mk_true()));
+        let bdd = mk_test_bdd(&worker);
+        let witness = worker.sat_witness(&bdd).unwrap();
+        assert!(is_satisfied(&worker, &bdd, &witness));
+    }
+
+    #[test]
+    fn bdd_sat_cubes() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        let cubes: Vec<Vec<Option<bool>>> = worker.sat_cubes(&bdd).collect();
+        assert_eq!(vec![
+            vec![Some(false), None, Some(true), None],
+            vec![Some(true), Some(true), None, None]
+        ], cubes);
+        assert_eq!(0, worker.sat_cubes(&worker.mk_false()).count());
+        assert_eq!(vec![vec![None; 4]], worker.sat_cubes(&worker.mk_true()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn bdd_sat_valuations() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        let valuations: Vec<Vec<bool>> = worker.sat_valuations(&bdd).collect();
+        assert_eq!(worker.exact_cardinality(&bdd), valuations.len() as u128);
+        // valuations are unique and satisfy the BDD
+        for (i, valuation) in valuations.iter().enumerate() {
+            assert!(is_satisfied(&worker, &bdd, valuation));
+            assert!(!valuations[(i + 1)..].contains(valuation));
+        }
+        assert_eq!(16, worker.sat_valuations(&worker.mk_true()).count());
+        assert_eq!(0, worker.sat_valuations(&worker.mk_false()).count());
+    }
+
+}
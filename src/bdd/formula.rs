@@ -0,0 +1,258 @@
+use super::{BDD, BDDWorker};
+use std::fmt::{Display, Formatter, Error};
+
+/// Error encountered while parsing a boolean formula. Columns are numbered from one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormulaError {
+    pub column: usize,
+    pub message: String
+}
+
+impl Display for FormulaError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_fmt(format_args!("Column {}: {}", self.column, self.message))
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Not, And, Or, Xor, Imp, Iff,
+    Open, Close
+}
+
+/// Token together with its column in the formula.
+type Positioned = (Token, usize);
+
+fn error<T>(column: usize, message: String) -> Result<T, FormulaError> {
+    return Err(FormulaError { column, message });
+}
+
+fn is_name_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_' || c == '.' || c == ':' || c == '\'';
+}
+
+/// Conversion between BDDs and boolean formulas over the named variables of the worker.
+///
+/// Formulas use `!`, `&`, `^`, `|`, `=>`, `<=>` (from the highest to the lowest priority),
+/// parentheses and constants `true`/`false` (or `1`/`0` if there are no such variables).
+/// Implication is right-associative, other binary operators are left-associative.
+impl BDDWorker {
+
+    /// Create a BDD corresponding to the given boolean [formula], e.g. `a & !(b | c) => d`.
+    pub fn eval_expression(&self, formula: &str) -> Result<BDD, FormulaError> {
+        let tokens = tokenize(formula)?;
+        let mut parser = Parser { worker: self, tokens: &tokens, position: 0, end_column: formula.chars().count() + 1 };
+        let result = parser.parse_iff()?;
+        if let Some((token, column)) = tokens.get(parser.position) {
+            return error(*column, format!("Unexpected {:?}.", token));
+        }
+        return Ok(result);
+    }
+
+    /// Print the given [bdd] as a boolean formula which can be read back using
+    /// [BDDWorker::eval_expression]. The formula is a disjunction of disjoint cubes
+    /// (see [BDDWorker::sat_cubes]).
+    pub fn to_string(&self, bdd: &BDD) -> String {
+        if self.is_false(bdd) { return "false".to_string() }
+        if self.is_true(bdd) { return "true".to_string() }
+        let cubes: Vec<String> = self.sat_cubes(bdd).map(|cube| {
+            let literals: Vec<String> = cube.iter().enumerate()
+                .filter_map(|(var, value)| value.map(|value| {
+                    if value { self.var_names[var].clone() } else { format!("!{}", self.var_names[var]) }
+                }))
+                .collect();
+            literals.join(" & ")
+        }).collect();
+        return cubes.join(" | ");
+    }
+
+}
+
+fn tokenize(formula: &str) -> Result<Vec<Positioned>, FormulaError> {
+    let mut result: Vec<Positioned> = Vec::new();
+    let chars: Vec<char> = formula.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        i += 1;
+        match c {
+            ' ' | '\t' => continue,
+            '!' => result.push((Token::Not, column)),
+            '&' => result.push((Token::And, column)),
+            '|' => result.push((Token::Or, column)),
+            '^' => result.push((Token::Xor, column)),
+            '(' => result.push((Token::Open, column)),
+            ')' => result.push((Token::Close, column)),
+            '=' if chars.get(i) == Some(&'>') => {
+                i += 1;
+                result.push((Token::Imp, column));
+            }
+            '<' if chars.get(i) == Some(&'=') && chars.get(i + 1) == Some(&'>') => {
+                i += 2;
+                result.push((Token::Iff, column));
+            }
+            _ if is_name_char(c) => {
+                let mut name = c.to_string();
+                while i < chars.len() && is_name_char(chars[i]) {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+                result.push((Token::Name(name), column));
+            }
+            _ => return error(column, format!("Unexpected character `{}`.", c))
+        }
+    }
+    return Ok(result);
+}
+
+/// Recursive descent parser which builds the BDD directly, one level per operator priority.
+struct Parser<'a> {
+    worker: &'a BDDWorker,
+    tokens: &'a [Positioned],
+    position: usize,
+    end_column: usize
+}
+
+impl Parser<'_> {
+
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.position).map(|(t, _)| t);
+    }
+
+    fn parse_iff(&mut self) -> Result<BDD, FormulaError> {
+        let mut result = self.parse_imp()?;
+        while self.peek() == Some(&Token::Iff) {
+            self.position += 1;
+            result = self.worker.mk_equals(&result, &self.parse_imp()?);
+        }
+        return Ok(result);
+    }
+
+    fn parse_imp(&mut self) -> Result<BDD, FormulaError> {
+        let result = self.parse_or()?;
+        if self.peek() == Some(&Token::Imp) {
+            self.position += 1;
+            return Ok(self.worker.mk_implies(&result, &self.parse_imp()?));
+        }
+        return Ok(result);
+    }
+
+    fn parse_or(&mut self) -> Result<BDD, FormulaError> {
+        let mut result = self.parse_xor()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            result = self.worker.mk_or(&result, &self.parse_xor()?);
+        }
+        return Ok(result);
+    }
+
+    fn parse_xor(&mut self) -> Result<BDD, FormulaError> {
+        let mut result = self.parse_and()?;
+        while self.peek() == Some(&Token::Xor) {
+            self.position += 1;
+            result = self.worker.mk_xor(&result, &self.parse_and()?);
+        }
+        return Ok(result);
+    }
+
+    fn parse_and(&mut self) -> Result<BDD, FormulaError> {
+        let mut result = self.parse_atom()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            result = self.worker.mk_and(&result, &self.parse_atom()?);
+        }
+        return Ok(result);
+    }
+
+    fn parse_atom(&mut self) -> Result<BDD, FormulaError> {
+        let (token, column) = match self.tokens.get(self.position) {
+            Some((token, column)) => (token.clone(), *column),
+            None => return error(self.end_column, "Unexpected end of formula.".to_string())
+        };
+        self.position += 1;
+        return match token {
+            Token::Not => Ok(self.worker.mk_not(&self.parse_atom()?)),
+            Token::Name(name) => match self.worker.var_index_mapping.get(&name) {
+                Some(index) => Ok(self.worker.mk_var(*index)),
+                None => match name.as_str() {
+                    "1" | "true" => Ok(self.worker.mk_true()),
+                    "0" | "false" => Ok(self.worker.mk_false()),
+                    _ => error(column, format!("Unknown variable {}.", name))
+                }
+            },
+            Token::Open => {
+                let inner = self.parse_iff()?;
+                match self.tokens.get(self.position) {
+                    Some((Token::Close, _)) => { self.position += 1; Ok(inner) },
+                    Some((token, column)) => error(*column, format!("Expected `)`, found {:?}.", token)),
+                    None => error(self.end_column, "Expected `)`, found end of formula.".to_string())
+                }
+            },
+            _ => error(column, format!("Unexpected {:?}.", token))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_worker() -> BDDWorker {
+        return BDDWorker::new(vec!["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()]);
+    }
+
+    fn parse_error(formula: &str) -> usize {
+        return mk_worker().eval_expression(formula).unwrap_err().column;
+    }
+
+    #[test]
+    fn bdd_eval_expression() {
+        let worker = mk_worker();
+        let (a, b, c, d) = (worker.mk_var(0), worker.mk_var(1), worker.mk_var(2), worker.mk_var(3));
+        let expected = worker.mk_implies(&worker.mk_and(&a, &worker.mk_not(&worker.mk_or(&b, &c))), &d);
+        assert_eq!(expected, worker.eval_expression("a & !(b | c) => d").unwrap());
+        assert_eq!(worker.mk_or(&a, &worker.mk_and(&b, &c)), worker.eval_expression("a | b & c").unwrap());
+        assert_eq!(worker.mk_or(&worker.mk_xor(&a, &b), &c), worker.eval_expression("a ^ b | c").unwrap());
+        // implication is right-associative
+        let expected = worker.mk_implies(&a, &worker.mk_implies(&b, &c));
+        assert_eq!(expected, worker.eval_expression("a => b => c").unwrap());
+        let expected = worker.mk_equals(&worker.mk_implies(&a, &b), &worker.mk_or(&worker.mk_not(&a), &b));
+        assert_eq!(expected, worker.eval_expression("a => b <=> !a | b").unwrap());
+        assert!(worker.is_true(&worker.eval_expression("(a => b) <=> (!a | b)").unwrap()));
+        assert!(worker.is_true(&worker.eval_expression("true | 0").unwrap()));
+        assert!(worker.is_false(&worker.eval_expression("false").unwrap()));
+    }
+
+    #[test]
+    fn bdd_eval_expression_errors() {
+        assert_eq!(5, parse_error("a & x"));
+        assert_eq!(3, parse_error("a $ b"));
+        assert_eq!(7, parse_error("(a & b"));
+        assert_eq!(3, parse_error("a b"));
+        assert_eq!(5, parse_error("a & "));
+        assert_eq!(3, parse_error("a = b"));
+        assert_eq!(1, parse_error(")"));
+        let e = mk_worker().eval_expression("a & !x").err().unwrap();
+        assert_eq!("Column 6: Unknown variable x.", e.to_string());
+    }
+
+    #[test]
+    fn bdd_to_string() {
+        let worker = mk_worker();
+        assert_eq!("true", worker.to_string(&worker.mk_true()));
+        assert_eq!("false", worker.to_string(&worker.mk_false()));
+        assert_eq!("!c", worker.to_string(&worker.mk_not(&worker.mk_var(2))));
+        let bdd = worker.eval_expression("a & b | !a & c").unwrap();
+        assert_eq!("!a & c | a & b", worker.to_string(&bdd));
+        for formula in &["a & !(b | c) => d", "a ^ b ^ c ^ d", "a <=> !d"] {
+            let bdd = worker.eval_expression(formula).unwrap();
+            assert_eq!(bdd, worker.eval_expression(&worker.to_string(&bdd)).unwrap());
+        }
+    }
+
+}
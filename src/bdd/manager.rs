@@ -0,0 +1,360 @@
+use super::{BDD, BDDWorker};
+use super::builder::{BDDBuilder, xor_lookup};
+use super::quantifiers::{quantify, RelationalProduct};
+use super::substitution::{flip_var, restrict};
+use std::collections::HashMap;
+
+/// Handle of a BDD stored in a [BDDManager]. Since all nodes of a manager are shared and
+/// reduced, two handles of the same manager are equal exactly when they represent
+/// the same function.
+///
+/// Handles are only valid within the manager which created them and only until the next
+/// [BDDManager::gc] which does not include them in its roots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BDDHandle {
+    index: usize
+}
+
+/// BDD manager is an alternative to [BDDWorker] for computations which repeatedly combine
+/// the same sub-BDDs. All BDDs live in one shared node table (with a unique table, so every
+/// node exists only once) and results of operations are cached across calls. For example,
+/// [crate::u32::symbolic::SymbolicAsyncGraph] computes all its images in one manager.
+///
+/// The node table only grows, hence unused nodes have to be removed explicitly using
+/// [BDDManager::gc]. A standalone [BDD] can be obtained using [BDDManager::export] and
+/// then used with a [BDDWorker] over the same variables.
+///
+/// Operations which do not produce BDDs (counting, witnesses) are delegated to a [BDDWorker]
+/// over the exported BDD.
+pub struct BDDManager {
+    worker: BDDWorker,
+    nodes: BDDBuilder,
+    and_cache: HashMap<(usize, usize), usize>,
+    or_cache: HashMap<(usize, usize), usize>,
+    xor_cache: HashMap<(usize, usize), usize>,
+    ite_cache: HashMap<(usize, usize, usize), usize>
+}
+
+impl BDDManager {
+
+    /// Create a new empty manager for BDDs over [num_vars] variables.
+    pub fn new(num_vars: u32) -> BDDManager {
+        return BDDManager::with_worker(BDDWorker::new_anonymous(num_vars));
+    }
+
+    /// Create a new empty manager for BDDs of the given [worker].
+    pub fn for_worker(worker: &BDDWorker) -> BDDManager {
+        return BDDManager::with_worker(BDDWorker::new(worker.var_names.clone()));
+    }
+
+    fn with_worker(worker: BDDWorker) -> BDDManager {
+        return BDDManager {
+            nodes: BDDBuilder::new(worker.num_vars),
+            worker,
+            and_cache: HashMap::new(),
+            or_cache: HashMap::new(),
+            xor_cache: HashMap::new(),
+            ite_cache: HashMap::new()
+        }
+    }
+
+    /// Number of nodes currently stored in this manager (including unused nodes
+    /// which will be removed by the next garbage collection).
+    pub fn node_count(&self) -> usize {
+        return self.nodes.size();
+    }
+
+    /// Copy the given standalone [bdd] into this manager.
+    pub fn import(&mut self, bdd: &BDD) -> BDDHandle {
+        return BDDHandle { index: self.nodes.import(bdd) };
+    }
+
+    /// Convert the BDD given by [handle] into a standalone [BDD].
+    pub fn export(&self, handle: &BDDHandle) -> BDD {
+        return self.nodes.export(handle.index);
+    }
+
+    pub fn mk_false(&self) -> BDDHandle {
+        return BDDHandle { index: 0 };
+    }
+
+    pub fn mk_true(&self) -> BDDHandle {
+        return BDDHandle { index: 1 };
+    }
+
+    /// Create a BDD corresponding to the `x` formula where `x` is the variable of the given index.
+    pub fn mk_var(&mut self, var_index: u32) -> BDDHandle {
+        if var_index >= self.worker.num_vars { self.worker.var_index_out_of_bounds(var_index) }
+        return BDDHandle { index: self.nodes.mk_node(var_index, 0, 1) };
+    }
+
+    pub fn is_false(&self, handle: &BDDHandle) -> bool {
+        return handle.index == 0;
+    }
+
+    pub fn is_true(&self, handle: &BDDHandle) -> bool {
+        return handle.index == 1;
+    }
+
+    /// Create a BDD corresponding to logical negation: `!bdd`.
+    pub fn mk_not(&mut self, bdd: &BDDHandle) -> BDDHandle {
+        return self.mk_xor(bdd, &BDDHandle { index: 1 });
+    }
+
+    /// Create a BDD corresponding to logical conjunction: `left & right`.
+    pub fn mk_and(&mut self, left: &BDDHandle, right: &BDDHandle) -> BDDHandle {
+        return BDDHandle { index: self.nodes.and(left.index, right.index, &mut self.and_cache) };
+    }
+
+    /// Create a BDD corresponding to logical disjunction: `left | right`.
+    pub fn mk_or(&mut self, left: &BDDHandle, right: &BDDHandle) -> BDDHandle {
+        return BDDHandle { index: self.nodes.or(left.index, right.index, &mut self.or_cache) };
+    }
+
+    /// Create a BDD corresponding to logical exclusive or: `left ^ right`.
+    pub fn mk_xor(&mut self, left: &BDDHandle, right: &BDDHandle) -> BDDHandle {
+        return BDDHandle { index: self.nodes.apply(left.index, right.index, &xor_lookup, &mut self.xor_cache) };
+    }
+
+    /// Create a BDD corresponding to logical implication: `left -> right`.
+    pub fn mk_implies(&mut self, left: &BDDHandle, right: &BDDHandle) -> BDDHandle {
+        let not_left = self.mk_not(left);
+        return self.mk_or(&not_left, right);
+    }
+
+    /// Create a BDD corresponding to logical equivalence: `left <-> right`.
+    pub fn mk_equals(&mut self, left: &BDDHandle, right: &BDDHandle) -> BDDHandle {
+        let xor = self.mk_xor(left, right);
+        return self.mk_not(&xor);
+    }
+
+    /// Create a BDD corresponding to `if cond then then_bdd else else_bdd`.
+    pub fn mk_ite(&mut self, cond: &BDDHandle, then_bdd: &BDDHandle, else_bdd: &BDDHandle) -> BDDHandle {
+        return BDDHandle { index: self.nodes.ite(cond.index, then_bdd.index, else_bdd.index, &mut self.ite_cache) };
+    }
+
+    /// Create a conjunction of all given BDDs. Conjunction of an empty list is `true`.
+    pub fn mk_and_all(&mut self, bdds: &[BDDHandle]) -> BDDHandle {
+        let mut result = self.mk_true();
+        for bdd in bdds {
+            result = self.mk_and(&result, bdd);
+        }
+        return result;
+    }
+
+    /// Create a disjunction of all given BDDs. Disjunction of an empty list is `false`.
+    pub fn mk_or_all(&mut self, bdds: &[BDDHandle]) -> BDDHandle {
+        let mut result = self.mk_false();
+        for bdd in bdds {
+            result = self.mk_or(&result, bdd);
+        }
+        return result;
+    }
+
+    /// Create a BDD over the given [vars] from an explicit [function],
+    /// see [BDDWorker::mk_from_function].
+    pub fn mk_from_function<F>(&mut self, vars: &[u32], function: F) -> BDDHandle
+        where F: FnMut(u64) -> bool
+    {
+        return self.import(&self.worker.mk_from_function(vars, function));
+    }
+
+    /// Create a BDD corresponding to `exists vars: bdd`.
+    pub fn exists(&mut self, bdd: &BDDHandle, vars: &[u32]) -> BDDHandle {
+        let mask = self.worker.var_mask(vars);
+        let result = quantify(&mut self.nodes, bdd.index, &mask, true, &mut HashMap::new(), &mut self.or_cache);
+        return BDDHandle { index: result };
+    }
+
+    /// Create a BDD corresponding to `forall vars: bdd`.
+    pub fn for_all(&mut self, bdd: &BDDHandle, vars: &[u32]) -> BDDHandle {
+        let mask = self.worker.var_mask(vars);
+        let result = quantify(&mut self.nodes, bdd.index, &mask, false, &mut HashMap::new(), &mut self.and_cache);
+        return BDDHandle { index: result };
+    }
+
+    /// Create a BDD corresponding to `exists vars: (left & right)` without constructing
+    /// the conjunction itself, see [BDDWorker::and_exists].
+    pub fn and_exists(&mut self, left: &BDDHandle, right: &BDDHandle, vars: &[u32]) -> BDDHandle {
+        let mask = self.worker.var_mask(vars);
+        let result = RelationalProduct::new(&mask).and_exists(&mut self.nodes, left.index, right.index);
+        return BDDHandle { index: result };
+    }
+
+    /// Create a BDD corresponding to `bdd[var/value]`.
+    pub fn restrict(&mut self, bdd: &BDDHandle, var: u32, value: bool) -> BDDHandle {
+        return self.restrict_cube(bdd, &[(var, value)]);
+    }
+
+    /// Create a BDD where all variables of the given [cube] are fixed to their respective
+    /// values. Panics if some variable does not exist or is fixed to two different values.
+    pub fn restrict_cube(&mut self, bdd: &BDDHandle, cube: &[(u32, bool)]) -> BDDHandle {
+        let values = self.worker.cube_values(cube);
+        return BDDHandle { index: restrict(&mut self.nodes, bdd.index, &values, &mut HashMap::new()) };
+    }
+
+    /// Create a BDD corresponding to `bdd[var/replacement]`.
+    pub fn compose(&mut self, bdd: &BDDHandle, var: u32, replacement: &BDDHandle) -> BDDHandle {
+        let positive = self.restrict(bdd, var, true);
+        let negative = self.restrict(bdd, var, false);
+        return self.mk_ite(replacement, &positive, &negative);
+    }
+
+    /// Create a BDD corresponding to `bdd[var/!var]`.
+    pub fn flip_var(&mut self, bdd: &BDDHandle, var: u32) -> BDDHandle {
+        if var >= self.worker.num_vars { self.worker.var_index_out_of_bounds(var) }
+        return BDDHandle { index: flip_var(&mut self.nodes, bdd.index, var, &mut HashMap::new()) };
+    }
+
+    /// Create a BDD where every variable `v` is replaced with variable `permutation[v]`.
+    /// Panics if [permutation] is not a permutation of all variables of this manager.
+    pub fn rename_variables(&mut self, bdd: &BDDHandle, permutation: &[u32]) -> BDDHandle {
+        self.worker.check_permutation(permutation);
+        let bdd = self.export(bdd);
+        let result = self.nodes.import_renamed(&bdd, permutation, &mut self.and_cache, &mut self.or_cache);
+        return BDDHandle { index: result };
+    }
+
+    /// Swap the unprimed and primed copies of all variables, see [BDDWorker::swap_primed].
+    pub fn swap_primed(&mut self, bdd: &BDDHandle) -> BDDHandle {
+        let permutation: Vec<u32> = (0..self.worker.num_vars).map(|v| v ^ 1).collect();
+        return self.rename_variables(bdd, &permutation);
+    }
+
+    /// Exact number of satisfying valuations of the given [bdd].
+    pub fn exact_cardinality(&self, bdd: &BDDHandle) -> u128 {
+        return self.worker.exact_cardinality(&self.export(bdd));
+    }
+
+    /// Find one satisfying valuation of the given [bdd], or [None] if the BDD is not
+    /// satisfiable. Don't care variables are set to false.
+    pub fn sat_witness(&self, bdd: &BDDHandle) -> Option<Vec<bool>> {
+        return self.worker.sat_witness(&self.export(bdd));
+    }
+
+    /// Remove all nodes which are not needed by the given [roots] and clear all operation
+    /// caches. Returns new handles of the roots (in the same order). All other handles
+    /// of this manager are invalidated.
+    pub fn gc(&mut self, roots: &[BDDHandle]) -> Vec<BDDHandle> {
+        let roots: Vec<usize> = roots.iter().map(|h| h.index).collect();
+        let new_roots = self.nodes.compact(&roots);
+        self.and_cache.clear();
+        self.or_cache.clear();
+        self.xor_cache.clear();
+        self.ite_cache.clear();
+        return new_roots.into_iter().map(|index| BDDHandle { index }).collect();
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_worker() -> BDDWorker {
+        return BDDWorker::new(vec!["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()]);
+    }
+
+    #[test]
+    fn bdd_manager_matches_worker() {
+        let worker = mk_worker();
+        let mut manager = BDDManager::for_worker(&worker);
+        let (a, b, c, d) = (manager.mk_var(0), manager.mk_var(1), manager.mk_var(2), manager.mk_var(3));
+        // a & !(b | c) => d
+        let b_or_c = manager.mk_or(&b, &c);
+        let not_b_or_c = manager.mk_not(&b_or_c);
+        let left = manager.mk_and(&a, &not_b_or_c);
+        let formula = manager.mk_implies(&left, &d);
+        assert_eq!(worker.eval_expression("a & !(b | c) => d").unwrap(), manager.export(&formula));
+        let equals = manager.mk_equals(&a, &d);
+        assert_eq!(worker.eval_expression("a <=> d").unwrap(), manager.export(&equals));
+        let ite = manager.mk_ite(&a, &b, &c);
+        assert_eq!(worker.eval_expression("a & b | !a & c").unwrap(), manager.export(&ite));
+        let exists = manager.exists(&ite, &[0]);
+        assert_eq!(worker.eval_expression("b | c").unwrap(), manager.export(&exists));
+        assert!(manager.is_false(&manager.mk_false()));
+        assert!(manager.is_true(&manager.mk_true()));
+        assert_eq!(worker.mk_true(), manager.export(&manager.mk_true()));
+        assert_eq!(worker.mk_false(), manager.export(&manager.mk_false()));
+    }
+
+    #[test]
+    fn bdd_manager_matches_worker_operations() {
+        let worker = mk_worker();
+        let mut manager = BDDManager::for_worker(&worker);
+        let left_bdd = worker.eval_expression("a & b | !a & c | d").unwrap();
+        let right_bdd = worker.eval_expression("(a <=> c) & !d").unwrap();
+        let (left, right) = (manager.import(&left_bdd), manager.import(&right_bdd));
+        let all = manager.mk_and_all(&[left, right]);
+        assert_eq!(worker.mk_and_all(&[left_bdd.clone(), right_bdd.clone()]), manager.export(&all));
+        let any = manager.mk_or_all(&[left, right]);
+        assert_eq!(worker.mk_or_all(&[left_bdd.clone(), right_bdd.clone()]), manager.export(&any));
+        assert_eq!(manager.mk_true(), manager.mk_and_all(&[]));
+        assert_eq!(manager.mk_false(), manager.mk_or_all(&[]));
+        let for_all = manager.for_all(&left, &[0, 3]);
+        assert_eq!(worker.for_all(&left_bdd, &[0, 3]), manager.export(&for_all));
+        let and_exists = manager.and_exists(&left, &right, &[0, 2]);
+        assert_eq!(worker.and_exists(&left_bdd, &right_bdd, &[0, 2]), manager.export(&and_exists));
+        let restricted = manager.restrict(&left, 0, false);
+        assert_eq!(worker.restrict(&left_bdd, 0, false), manager.export(&restricted));
+        let restricted = manager.restrict_cube(&left, &[(0, true), (3, false)]);
+        assert_eq!(worker.restrict_cube(&left_bdd, &[(0, true), (3, false)]), manager.export(&restricted));
+        let composed = manager.compose(&left, 1, &right);
+        assert_eq!(worker.compose(&left_bdd, 1, &right_bdd), manager.export(&composed));
+        let flipped = manager.flip_var(&left, 2);
+        assert_eq!(worker.flip_var(&left_bdd, 2), manager.export(&flipped));
+        let renamed = manager.rename_variables(&left, &[3, 0, 1, 2]);
+        assert_eq!(worker.rename_variables(&left_bdd, &[3, 0, 1, 2]), manager.export(&renamed));
+        let swapped = manager.swap_primed(&right);
+        assert_eq!(worker.swap_primed(&right_bdd), manager.export(&swapped));
+        let function = manager.mk_from_function(&[1, 3], |x| x != 2);
+        assert_eq!(worker.mk_from_function(&[1, 3], |x| x != 2), manager.export(&function));
+        assert_eq!(worker.exact_cardinality(&left_bdd), manager.exact_cardinality(&left));
+        assert_eq!(worker.sat_witness(&right_bdd), manager.sat_witness(&right));
+        assert_eq!(None, manager.sat_witness(&manager.mk_false()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_manager_flip_unknown_variable() {
+        let mut manager = BDDManager::new(3);
+        let var = manager.mk_var(0);
+        manager.flip_var(&var, 3);
+    }
+
+    #[test]
+    fn bdd_manager_canonical_handles() {
+        let worker = mk_worker();
+        let mut manager = BDDManager::for_worker(&worker);
+        let imported = manager.import(&worker.eval_expression("a ^ b ^ c").unwrap());
+        let (a, b, c) = (manager.mk_var(0), manager.mk_var(1), manager.mk_var(2));
+        let b_xor_c = manager.mk_xor(&b, &c);
+        let computed = manager.mk_xor(&a, &b_xor_c);
+        assert_eq!(imported, computed);
+        let not_a = manager.mk_not(&a);
+        let contradiction = manager.mk_and(&a, &not_a);
+        assert!(manager.is_false(&contradiction));
+        let tautology = manager.mk_or(&a, &not_a);
+        assert!(manager.is_true(&tautology));
+    }
+
+    #[test]
+    fn bdd_manager_gc() {
+        let worker = mk_worker();
+        let mut manager = BDDManager::for_worker(&worker);
+        let kept = manager.import(&worker.eval_expression("a & b | c & d").unwrap());
+        manager.import(&worker.eval_expression("a ^ b ^ c ^ d").unwrap());
+        let before = manager.node_count();
+        let exported = manager.export(&kept);
+        let roots = manager.gc(&[kept, manager.mk_true()]);
+        assert!(manager.node_count() < before);
+        assert_eq!(exported.size(), manager.node_count());
+        assert_eq!(exported, manager.export(&roots[0]));
+        assert!(manager.is_true(&roots[1]));
+        // the manager is still usable after garbage collection
+        let c = manager.mk_var(2);
+        let conjunction = manager.mk_and(&roots[0], &c);
+        assert_eq!(worker.eval_expression("(a & b | c & d) & c").unwrap(), manager.export(&conjunction));
+    }
+
+}
@@ -1,6 +1,23 @@
 use std::collections::HashMap;
 
 mod dot_printer;
+mod builder;
+mod quantifiers;
+mod substitution;
+mod renaming;
+mod counting;
+mod operations;
+mod formula;
+mod serialization;
+mod validation;
+mod reordering;
+mod manager;
+
+pub use self::counting::{SatCubes, SatValuations};
+pub use self::formula::FormulaError;
+pub use self::serialization::BDDFormatError;
+pub use self::validation::BDDValidationError;
+pub use self::manager::{BDDManager, BDDHandle};
 
 /// BDD Node represents one vertex of the BDD DAG. It specifies the variable upon which
 /// we are conditioning and two pointers into the BDD itself. Hence every node can only
@@ -73,7 +90,7 @@ pub struct BDD(Vec<BDDNode>);
 impl BDD {
 
     /// Number of nodes in this BDD
-    fn size(&self) -> usize {
+    pub fn size(&self) -> usize {
         return self.0.len();
     }
 
@@ -249,7 +266,7 @@ impl BDDWorker {
     /// Create a BDD corresponding to logical implication: `left -> right`.
     pub fn mk_implies(&self, left: &BDD, right: &BDD) -> BDD {
         return self.apply(left, right, |l, r| -> Option<bool> {
-            if l.is_zero() { Some(true) }
+            if l.is_zero() || r.is_one() { Some(true) }
             else if l.is_one() && r.is_zero() { Some(false) }
             else { None }
         })
@@ -410,6 +427,14 @@ impl BDDWorker {
 mod tests {
     use super::*;
 
+    /// Formula (x0 & x1) | (!x0 & x2) over 4 variables, shared by tests of the BDD operations.
+    pub(super) fn mk_test_bdd(worker: &BDDWorker) -> BDD {
+        let x0 = worker.mk_var(0);
+        let left = worker.mk_and(&x0, &worker.mk_var(1));
+        let right = worker.mk_and(&worker.mk_not(&x0), &worker.mk_var(2));
+        return worker.mk_or(&left, &right);
+    }
+
     // A small BDD over variables x0..x4 corresponding to formula (x4 & !x3)
     fn mk_small_test_bdd() -> BDD {
         return BDD(vec![
@@ -543,4 +568,17 @@ mod tests {
         assert!(worker.is_false(&and));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn bdd_mk_implies() {
+        let worker = BDDWorker::new_anonymous(2);
+        let (tt, ff) = (worker.mk_true(), worker.mk_false());
+        let x = worker.mk_var(0);
+        assert_eq!(tt, worker.mk_implies(&tt, &tt));
+        assert_eq!(ff, worker.mk_implies(&tt, &ff));
+        assert_eq!(tt, worker.mk_implies(&ff, &x));
+        assert_eq!(x, worker.mk_implies(&tt, &x));
+        assert_eq!(tt, worker.mk_implies(&x, &x));
+        assert_eq!(worker.mk_not(&x), worker.mk_implies(&x, &ff));
+    }
+
+}
@@ -0,0 +1,184 @@
+use super::{BDD, BDDWorker};
+use super::builder::BDDBuilder;
+use std::collections::HashMap;
+
+/// Operations which combine more than two BDDs. Unlike repeated binary operations, all
+/// intermediate results share one node table and one operation cache.
+impl BDDWorker {
+
+    /// Create a BDD corresponding to `if cond then then_bdd else else_bdd`, i.e.
+    /// `(cond & then_bdd) | (!cond & else_bdd)`, computed in one traversal.
+    pub fn mk_ite(&self, cond: &BDD, then_bdd: &BDD, else_bdd: &BDD) -> BDD {
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let cond = builder.import(cond);
+        let then_node = builder.import(then_bdd);
+        let else_node = builder.import(else_bdd);
+        let result = builder.ite(cond, then_node, else_node, &mut HashMap::new());
+        return builder.export(result);
+    }
+
+    /// Create a conjunction of all given BDDs. Conjunction of an empty list is `true`.
+    pub fn mk_and_all(&self, bdds: &[BDD]) -> BDD {
+        return self.mk_balanced(bdds, 1, BDDBuilder::and);
+    }
+
+    /// Create a disjunction of all given BDDs. Disjunction of an empty list is `false`.
+    pub fn mk_or_all(&self, bdds: &[BDD]) -> BDD {
+        return self.mk_balanced(bdds, 0, BDDBuilder::or);
+    }
+
+    /// Create a BDD over the given [vars] (which must be sorted in increasing order) from
+    /// an explicit [function]. Bit `i` of the argument of [function] is the value of
+    /// `vars[i]`, hence [function] is evaluated for all `2^vars.len()` valuations. Other
+    /// variables are not used by the result.
+    pub fn mk_from_function<F>(&self, vars: &[u32], mut function: F) -> BDD
+        where F: FnMut(u64) -> bool
+    {
+        if vars.len() >= 64 { panic!("Cannot create BDD from a function of {} variables.", vars.len()) }
+        for i in 0..vars.len() {
+            if vars[i] >= self.num_vars { self.var_index_out_of_bounds(vars[i]) }
+            if i > 0 && vars[i - 1] >= vars[i] { panic!("Variables {:?} are not sorted.", vars) }
+        }
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = mk_from_function(&mut builder, vars, 0, 0, &mut function);
+        return builder.export(root);
+    }
+
+    /// Create a BDD over the given [vars] (which must be sorted in increasing order) which
+    /// is satisfied exactly by the given [valuations], encoded as in
+    /// [BDDWorker::mk_from_function]. Unlike [BDDWorker::mk_from_function], the cost only
+    /// depends on the number of valuations, not on the number of all `2^vars.len()` valuations.
+    pub fn mk_from_valuations(&self, vars: &[u32], valuations: &[u64]) -> BDD {
+        if vars.len() >= 64 { panic!("Cannot create BDD from valuations of {} variables.", vars.len()) }
+        for i in 0..vars.len() {
+            if vars[i] >= self.num_vars { self.var_index_out_of_bounds(vars[i]) }
+            if i > 0 && vars[i - 1] >= vars[i] { panic!("Variables {:?} are not sorted.", vars) }
+        }
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = mk_from_valuations(&mut builder, vars, 0, &mut valuations.to_vec());
+        return builder.export(root);
+    }
+
+    /// Combine all [bdds] using [operation] in a balanced binary tree, so that the
+    /// intermediate results stay as small as possible. [neutral] is the result for an empty list.
+    fn mk_balanced<F>(&self, bdds: &[BDD], neutral: usize, operation: F) -> BDD
+        where F: Fn(&mut BDDBuilder, usize, usize, &mut HashMap<(usize, usize), usize>) -> usize
+    {
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let mut cache: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut nodes: Vec<usize> = bdds.iter().map(|bdd| builder.import(bdd)).collect();
+        if nodes.is_empty() { nodes.push(neutral) }
+        while nodes.len() > 1 {
+            nodes = nodes.chunks(2).map(|pair| {
+                if pair.len() == 2 { operation(&mut builder, pair[0], pair[1], &mut cache) } else { pair[0] }
+            }).collect();
+        }
+        return builder.export(nodes[0]);
+    }
+
+}
+
+/// Build the sub-graph for valuations where the first [depth] variables are fixed to
+/// the bits of [prefix]. Variables are sorted, so the first variable is the root.
+fn mk_from_function<F>(builder: &mut BDDBuilder, vars: &[u32], depth: usize, prefix: u64, function: &mut F) -> usize
+    where F: FnMut(u64) -> bool
+{
+    if depth == vars.len() {
+        return if function(prefix) { 1 } else { 0 };
+    }
+    let low = mk_from_function(builder, vars, depth + 1, prefix, function);
+    let high = mk_from_function(builder, vars, depth + 1, prefix | (1 << depth), function);
+    return builder.mk_node(vars[depth], low, high);
+}
+
+/// Build the sub-graph for [valuations] which all agree on the first [depth] variables.
+/// The valuations are reordered in place, so that each half can be processed recursively.
+fn mk_from_valuations(builder: &mut BDDBuilder, vars: &[u32], depth: usize, valuations: &mut [u64]) -> usize {
+    if valuations.is_empty() { return 0 }
+    if depth == vars.len() { return 1 }
+    // move valuations where vars[depth] is false to the front
+    let mut split = 0;
+    for i in 0..valuations.len() {
+        if valuations[i] & (1 << depth) == 0 {
+            valuations.swap(split, i);
+            split += 1;
+        }
+    }
+    let (low_valuations, high_valuations) = valuations.split_at_mut(split);
+    let low = mk_from_valuations(builder, vars, depth + 1, low_valuations);
+    let high = mk_from_valuations(builder, vars, depth + 1, high_valuations);
+    return builder.mk_node(vars[depth], low, high);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bdd_mk_ite() {
+        let worker = BDDWorker::new_anonymous(4);
+        let (x0, x1, x2) = (worker.mk_var(0), worker.mk_var(1), worker.mk_var(2));
+        let expected = worker.mk_or(&worker.mk_and(&x0, &x1), &worker.mk_and(&worker.mk_not(&x0), &x2));
+        assert_eq!(expected, worker.mk_ite(&x0, &x1, &x2));
+        // condition below the branches in the variable ordering
+        let expected = worker.mk_or(&worker.mk_and(&x2, &x0), &worker.mk_and(&worker.mk_not(&x2), &x1));
+        assert_eq!(expected, worker.mk_ite(&x2, &x0, &x1));
+        assert_eq!(x0, worker.mk_ite(&x0, &worker.mk_true(), &worker.mk_false()));
+        assert_eq!(worker.mk_not(&x0), worker.mk_ite(&x0, &worker.mk_false(), &worker.mk_true()));
+        assert_eq!(x1, worker.mk_ite(&worker.mk_true(), &x1, &x2));
+        assert_eq!(x2, worker.mk_ite(&worker.mk_false(), &x1, &x2));
+        assert_eq!(x1, worker.mk_ite(&x0, &x1, &x1));
+    }
+
+    #[test]
+    fn bdd_mk_from_function() {
+        let worker = BDDWorker::new_anonymous(5);
+        // x1 & !x3 | x4, with bit 0 = x1, bit 1 = x3, bit 2 = x4
+        let bdd = worker.mk_from_function(&[1, 3, 4], |v| (v & 0b1 != 0 && v & 0b10 == 0) || v & 0b100 != 0);
+        let expected = worker.mk_or(&worker.mk_and(&worker.mk_var(1), &worker.mk_not(&worker.mk_var(3))), &worker.mk_var(4));
+        assert_eq!(expected, bdd);
+        assert!(worker.is_true(&worker.mk_from_function(&[0, 2], |_| true)));
+        assert!(worker.is_false(&worker.mk_from_function(&[0, 2], |_| false)));
+        assert!(worker.is_true(&worker.mk_from_function(&[], |_| true)));
+    }
+
+    #[test]
+    fn bdd_mk_from_valuations() {
+        let worker = BDDWorker::new_anonymous(5);
+        for modulus in 1..6 {
+            let function = |v: u64| v % modulus == 2 % modulus;
+            let valuations: Vec<u64> = (0..8).rev().filter(|v| function(*v)).collect();
+            assert_eq!(worker.mk_from_function(&[0, 2, 3], function), worker.mk_from_valuations(&[0, 2, 3], &valuations));
+        }
+        assert!(worker.is_false(&worker.mk_from_valuations(&[0, 2], &[])));
+        assert!(worker.is_true(&worker.mk_from_valuations(&[0, 2], &[3, 1, 0, 2, 1])));
+        assert!(worker.is_true(&worker.mk_from_valuations(&[], &[0])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_mk_from_function_unsorted() {
+        let worker = BDDWorker::new_anonymous(5);
+        worker.mk_from_function(&[2, 1], |_| true);
+    }
+
+    #[test]
+    fn bdd_mk_and_or_all() {
+        let worker = BDDWorker::new_anonymous(5);
+        let vars: Vec<BDD> = (0..5).map(|v| worker.mk_var(v)).collect();
+        let mut conjunction = worker.mk_true();
+        let mut disjunction = worker.mk_false();
+        for var in &vars {
+            conjunction = worker.mk_and(&conjunction, var);
+            disjunction = worker.mk_or(&disjunction, var);
+        }
+        assert_eq!(conjunction, worker.mk_and_all(&vars));
+        assert_eq!(disjunction, worker.mk_or_all(&vars));
+        assert_eq!(vars[3], worker.mk_and_all(&vars[3..4]));
+        assert!(worker.is_true(&worker.mk_and_all(&[])));
+        assert!(worker.is_false(&worker.mk_or_all(&[])));
+        let contradiction = vec![vars[0].clone(), vars[1].clone(), worker.mk_not(&vars[0])];
+        assert!(worker.is_false(&worker.mk_and_all(&contradiction)));
+    }
+
+}
@@ -0,0 +1,242 @@
+use super::{BDD, BDDWorker};
+use super::builder::BDDBuilder;
+use std::collections::HashMap;
+
+/// Quantification removes variables from a BDD. Existential quantification `exists x: phi`
+/// corresponds to `phi[x/0] | phi[x/1]`, universal quantification `forall x: phi` to
+/// `phi[x/0] & phi[x/1]`. The resulting BDDs are canonical and in the same post-order layout
+/// as BDDs produced by other operations of [BDDWorker].
+impl BDDWorker {
+
+    /// Create a BDD corresponding to `exists vars: (left & right)`, also known as relational
+    /// product. The conjunction and quantification are performed in one traversal, hence
+    /// the (possibly large) conjunction itself is never constructed.
+    pub fn and_exists(&self, left: &BDD, right: &BDD, vars: &[u32]) -> BDD {
+        let mask = self.var_mask(vars);
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let left = builder.import(left);
+        let right = builder.import(right);
+        let mut product = RelationalProduct::new(&mask);
+        let result = product.and_exists(&mut builder, left, right);
+        return builder.export(result);
+    }
+
+    /// Create a BDD corresponding to `exists vars: bdd`.
+    pub fn exists(&self, bdd: &BDD, vars: &[u32]) -> BDD {
+        return self.quantify(bdd, vars, true);
+    }
+
+    /// Create a BDD corresponding to `forall vars: bdd`.
+    pub fn for_all(&self, bdd: &BDD, vars: &[u32]) -> BDD {
+        return self.quantify(bdd, vars, false);
+    }
+
+    /// Create a vector where `vars[i]` is true when variable `i` is in the given list.
+    /// Panics if some variable does not exist.
+    pub(super) fn var_mask(&self, vars: &[u32]) -> Vec<bool> {
+        let mut mask = vec![false; self.num_vars as usize];
+        for var in vars {
+            if *var >= self.num_vars { self.var_index_out_of_bounds(*var) }
+            mask[*var as usize] = true;
+        }
+        return mask;
+    }
+
+    fn quantify(&self, bdd: &BDD, vars: &[u32], existential: bool) -> BDD {
+        let mask = self.var_mask(vars);
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = builder.import(bdd);
+        let result = quantify(&mut builder, root, &mask, existential, &mut HashMap::new(), &mut HashMap::new());
+        return builder.export(result);
+    }
+
+}
+
+/// Quantify the variables given by [mask] in the sub-graph of the given [node]. The results are
+/// cached in [finished], while [op_cache] is used by the and/or operations merging the cofactors.
+pub(super) fn quantify(
+    builder: &mut BDDBuilder,
+    node: usize,
+    mask: &[bool],
+    existential: bool,
+    finished: &mut HashMap<usize, usize>,
+    op_cache: &mut HashMap<(usize, usize), usize>
+) -> usize {
+    if node <= 1 { return node }
+    if let Some(result) = finished.get(&node) { return *result }
+    let var = builder.var(node);
+    let (low, high) = (builder.low(node), builder.high(node));
+    let new_low = quantify(builder, low, mask, existential, finished, op_cache);
+    let new_high = quantify(builder, high, mask, existential, finished, op_cache);
+    let result = if !mask[var as usize] {
+        builder.mk_node(var, new_low, new_high)
+    } else if existential {
+        builder.or(new_low, new_high, op_cache)
+    } else {
+        builder.and(new_low, new_high, op_cache)
+    };
+    finished.insert(node, result);
+    return result;
+}
+
+/// Caches of one relational product computation. The caches are only valid for one
+/// [mask], but they can be reused by several products in the same builder.
+pub(super) struct RelationalProduct<'a> {
+    mask: &'a [bool],
+    // results of and_exists for pairs of nodes
+    finished: HashMap<(usize, usize), usize>,
+    // results of quantification of single nodes (when the other operand is `one`)
+    quantified: HashMap<usize, usize>,
+    // cache of the disjunction merging quantified cofactors
+    op_cache: HashMap<(usize, usize), usize>
+}
+
+impl RelationalProduct<'_> {
+
+    pub(super) fn new(mask: &[bool]) -> RelationalProduct<'_> {
+        return RelationalProduct {
+            mask,
+            finished: HashMap::new(),
+            quantified: HashMap::new(),
+            op_cache: HashMap::new()
+        }
+    }
+
+    /// Compute `exists mask: (left & right)` for two nodes of the given [builder].
+    pub(super) fn and_exists(&mut self, builder: &mut BDDBuilder, left: usize, right: usize) -> usize {
+        if left == 0 || right == 0 { return 0 }
+        if left == 1 || left == right {
+            return quantify(builder, right, self.mask, true, &mut self.quantified, &mut self.op_cache);
+        }
+        if right == 1 {
+            return quantify(builder, left, self.mask, true, &mut self.quantified, &mut self.op_cache);
+        }
+        // the operation is symmetric, so we can normalize the cache key
+        let key = if left < right { (left, right) } else { (right, left) };
+        if let Some(result) = self.finished.get(&key) { return *result }
+
+        let (var_left, var_right) = (builder.var(left), builder.var(right));
+        let var = std::cmp::min(var_left, var_right);
+        let (left_low, left_high) = if var_left == var { (builder.low(left), builder.high(left)) } else { (left, left) };
+        let (right_low, right_high) = if var_right == var { (builder.low(right), builder.high(right)) } else { (right, right) };
+
+        let low = self.and_exists(builder, left_low, right_low);
+        let result = if self.mask[var as usize] {
+            if low == 1 {
+                // the disjunction is already true, no need to explore the high cofactor
+                1
+            } else {
+                let high = self.and_exists(builder, left_high, right_high);
+                builder.or(low, high, &mut self.op_cache)
+            }
+        } else {
+            let high = self.and_exists(builder, left_high, right_high);
+            builder.mk_node(var, low, high)
+        };
+        self.finished.insert(key, result);
+        return result;
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdd::tests::mk_test_bdd;
+    use rand::prelude::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    #[test]
+    fn bdd_exists_single_variable() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        // exists x0: (x0 & x1) | (!x0 & x2) = x1 | x2
+        assert_eq!(worker.mk_or(&worker.mk_var(1), &worker.mk_var(2)), worker.exists(&bdd, &[0]));
+        // exists x1: (x0 & x1) | (!x0 & x2) = x0 | x2
+        assert_eq!(worker.mk_or(&worker.mk_var(0), &worker.mk_var(2)), worker.exists(&bdd, &[1]));
+        // x3 does not appear in the formula
+        assert_eq!(bdd, worker.exists(&bdd, &[3]));
+    }
+
+    #[test]
+    fn bdd_for_all_single_variable() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        // forall x0: (x0 & x1) | (!x0 & x2) = x1 & x2
+        assert_eq!(worker.mk_and(&worker.mk_var(1), &worker.mk_var(2)), worker.for_all(&bdd, &[0]));
+        // forall x2: (x0 & x1) | (!x0 & x2) = x0 & x1
+        assert_eq!(worker.mk_and(&worker.mk_var(0), &worker.mk_var(1)), worker.for_all(&bdd, &[2]));
+    }
+
+    #[test]
+    fn bdd_quantify_all_variables() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        assert!(worker.is_true(&worker.exists(&bdd, &[0, 1, 2])));
+        assert!(worker.is_false(&worker.for_all(&bdd, &[0, 1, 2])));
+        assert!(worker.is_true(&worker.exists(&bdd, &[1, 2])));
+        assert!(worker.is_false(&worker.for_all(&bdd, &[1, 2])));
+        assert!(worker.is_false(&worker.exists(&worker.mk_false(), &[0])));
+        assert!(worker.is_true(&worker.for_all(&worker.mk_true(), &[0])));
+    }
+
+    #[test]
+    fn bdd_quantifier_duality() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        // forall x: phi = !exists x: !phi
+        for vars in &[vec![0], vec![1], vec![0, 2], vec![3]] {
+            let expected = worker.mk_not(&worker.exists(&worker.mk_not(&bdd), vars));
+            assert_eq!(expected, worker.for_all(&bdd, vars));
+        }
+    }
+
+    // Build a random formula in DNF with the given number of clauses.
+    fn mk_random_bdd(worker: &BDDWorker, rnd: &mut StdRng, clauses: usize) -> BDD {
+        let mut result = worker.mk_false();
+        for _ in 0..clauses {
+            let mut clause = worker.mk_true();
+            for var in 0..worker.num_vars {
+                match rnd.next_u32() % 3 {
+                    0 => clause = worker.mk_and(&clause, &worker.mk_var(var)),
+                    1 => clause = worker.mk_and(&clause, &worker.mk_not(&worker.mk_var(var))),
+                    _ => ()
+                }
+            }
+            result = worker.mk_or(&result, &clause);
+        }
+        return result;
+    }
+
+    #[test]
+    fn bdd_and_exists_matches_and_then_exists() {
+        let worker = BDDWorker::new_anonymous(6);
+        let mut rnd = StdRng::seed_from_u64(1234567890);
+        for _ in 0..50 {
+            let left = mk_random_bdd(&worker, &mut rnd, 4);
+            let right = mk_random_bdd(&worker, &mut rnd, 4);
+            let vars: Vec<u32> = (0..6).filter(|_| rnd.next_u32() % 2 == 0).collect();
+            let expected = worker.exists(&worker.mk_and(&left, &right), &vars);
+            assert_eq!(expected, worker.and_exists(&left, &right, &vars));
+        }
+    }
+
+    #[test]
+    fn bdd_and_exists_terminals() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        assert!(worker.is_false(&worker.and_exists(&bdd, &worker.mk_false(), &[0])));
+        assert_eq!(worker.exists(&bdd, &[0]), worker.and_exists(&bdd, &worker.mk_true(), &[0]));
+        assert_eq!(worker.exists(&bdd, &[1]), worker.and_exists(&worker.mk_true(), &bdd, &[1]));
+        assert!(worker.is_false(&worker.and_exists(&bdd, &worker.mk_not(&bdd), &[0, 1, 2, 3])));
+        assert!(worker.is_true(&worker.and_exists(&bdd, &bdd, &[0, 1, 2, 3])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_exists_unknown_variable() {
+        let worker = BDDWorker::new_anonymous(2);
+        worker.exists(&worker.mk_var(0), &[2]);
+    }
+
+}
@@ -0,0 +1,132 @@
+use super::{BDD, BDDWorker};
+use super::builder::BDDBuilder;
+use crate::u32::bn::{BooleanNetwork, VariableId};
+use std::collections::HashMap;
+
+/// Renaming maps every variable of a BDD to a different variable. When the mapping does not
+/// preserve the variable ordering, the BDD has to be rebuilt, since the renamed nodes would
+/// violate the ordering invariant.
+///
+/// To encode transition relations of a [BooleanNetwork], the worker can be also created with
+/// two interleaved copies of network variables: variable `x` of the network is represented by
+/// BDD variable `2*x` (unprimed, current state) and `2*x + 1` (primed, next state).
+impl BDDWorker {
+
+    /// Create a new BDD worker with interleaved unprimed and primed copies of all variables
+    /// of the given [network]. The primed copy of variable `x` is named `x'`.
+    pub fn new_primed(network: &BooleanNetwork) -> BDDWorker {
+        let mut names: Vec<String> = Vec::new();
+        for index in 0..(network.var_count() as usize) {
+            let name = network.get_variable_name(&VariableId::from_index(index));
+            names.push(name.clone());
+            names.push(format!("{}'", name));
+        }
+        return BDDWorker::new(names);
+    }
+
+    /// Index of the unprimed (current state) BDD variable of the given network [var].
+    pub fn unprimed_var(var: &VariableId) -> u32 {
+        return 2 * (var.index() as u32);
+    }
+
+    /// Index of the primed (next state) BDD variable of the given network [var].
+    pub fn primed_var(var: &VariableId) -> u32 {
+        return 2 * (var.index() as u32) + 1;
+    }
+
+    /// Create a BDD where every variable `v` is replaced with variable `permutation[v]`.
+    /// Panics if [permutation] is not a permutation of all variables of this worker.
+    pub fn rename_variables(&self, bdd: &BDD, permutation: &[u32]) -> BDD {
+        self.check_permutation(permutation);
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = builder.import_renamed(bdd, permutation, &mut HashMap::new(), &mut HashMap::new());
+        return builder.export(root);
+    }
+
+    /// Swap the unprimed and primed copies of all variables, assuming this worker was created
+    /// using [BDDWorker::new_primed]. This turns a set of successor states into a set of
+    /// current states and vice versa.
+    pub fn swap_primed(&self, bdd: &BDD) -> BDD {
+        let permutation: Vec<u32> = (0..self.num_vars).map(|v| v ^ 1).collect();
+        return self.rename_variables(bdd, &permutation);
+    }
+
+    /// Panics if [permutation] is not a permutation of all variables of this worker.
+    pub(super) fn check_permutation(&self, permutation: &[u32]) {
+        if permutation.len() != self.num_vars as usize {
+            panic!("Permutation of {} variables given, but worker has {} variables.", permutation.len(), self.num_vars);
+        }
+        let mut is_used = vec![false; self.num_vars as usize];
+        for var in permutation {
+            if *var >= self.num_vars { self.var_index_out_of_bounds(*var) }
+            if is_used[*var as usize] { panic!("Variable {} appears twice in a permutation.", var) }
+            is_used[*var as usize] = true;
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::bn::AsyncNetwork;
+    use crate::u32::models::demo_model_bistable;
+
+    #[test]
+    fn bdd_rename_preserving_order() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = worker.mk_and(&worker.mk_var(0), &worker.mk_not(&worker.mk_var(2)));
+        let expected = worker.mk_and(&worker.mk_var(1), &worker.mk_not(&worker.mk_var(3)));
+        assert_eq!(expected, worker.rename_variables(&bdd, &[1, 0, 3, 2]));
+        assert_eq!(bdd, worker.rename_variables(&bdd, &[0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn bdd_rename_changing_order() {
+        let worker = BDDWorker::new_anonymous(4);
+        // (x0 & x1) | (!x0 & x2), with x0 -> x3, x1 -> x0, x2 -> x1, x3 -> x2
+        let x0 = worker.mk_var(0);
+        let bdd = worker.mk_or(
+            &worker.mk_and(&x0, &worker.mk_var(1)),
+            &worker.mk_and(&worker.mk_not(&x0), &worker.mk_var(2))
+        );
+        let x3 = worker.mk_var(3);
+        let expected = worker.mk_or(
+            &worker.mk_and(&x3, &worker.mk_var(0)),
+            &worker.mk_and(&worker.mk_not(&x3), &worker.mk_var(1))
+        );
+        let renamed = worker.rename_variables(&bdd, &[3, 0, 1, 2]);
+        assert_eq!(expected, renamed);
+        // renaming back using the inverse permutation gives the original BDD
+        assert_eq!(bdd, worker.rename_variables(&renamed, &[1, 2, 3, 0]));
+        assert!(worker.is_true(&worker.rename_variables(&worker.mk_true(), &[3, 0, 1, 2])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_rename_not_permutation() {
+        let worker = BDDWorker::new_anonymous(3);
+        worker.rename_variables(&worker.mk_var(0), &[0, 1, 1]);
+    }
+
+    #[test]
+    fn bdd_primed_variables() {
+        let network = demo_model_bistable();
+        let worker = BDDWorker::new_primed(&network);
+        let a = network.find_variable("DNA").unwrap();
+        let a_name = network.get_variable_name(&a).clone();
+        assert_eq!(worker.mk_var(BDDWorker::unprimed_var(&a)), worker.mk_named_var(&a_name));
+        assert_eq!(worker.mk_var(BDDWorker::primed_var(&a)), worker.mk_named_var(&format!("{}'", a_name)));
+        let state = worker.mk_and(
+            &worker.mk_var(BDDWorker::unprimed_var(&a)),
+            &worker.mk_not(&worker.mk_var(BDDWorker::primed_var(&a)))
+        );
+        let swapped = worker.mk_and(
+            &worker.mk_var(BDDWorker::primed_var(&a)),
+            &worker.mk_not(&worker.mk_var(BDDWorker::unprimed_var(&a)))
+        );
+        assert_eq!(swapped, worker.swap_primed(&state));
+        assert_eq!(state, worker.swap_primed(&swapped));
+    }
+
+}
@@ -0,0 +1,304 @@
+use super::{BDD, BDDWorker};
+use super::builder::BDDBuilder;
+use std::collections::HashMap;
+
+/// The variable ordering of a [BDDWorker] is given by the order of its variables. To change
+/// the ordering, create a new worker with a different order of variable names (for example
+/// computed using [BDDWorker::sift_order]) and [BDDWorker::transfer] the BDDs into it.
+impl BDDWorker {
+
+    /// Create a BDD of this worker representing the same function as the [bdd] created by
+    /// the [source] worker. Variables are matched by name, hence the two workers can use
+    /// different variable orderings. Panics if some variable used by the [bdd] does not
+    /// exist in this worker.
+    pub fn transfer(&self, bdd: &BDD, source: &BDDWorker) -> BDD {
+        let mut is_used = vec![false; source.num_vars as usize];
+        for i in 2..bdd.size() {
+            is_used[bdd.var(i)] = true;
+        }
+        // unused variables are never looked up, so their mapping does not matter
+        let mapping: Vec<u32> = (0..source.num_vars as usize).map(|var| {
+            if !is_used[var] { return 0 }
+            let name = &source.var_names[var];
+            return match self.var_index_mapping.get(name) {
+                Some(index) => *index,
+                None => self.var_name_out_of_bounds(name)
+            }
+        }).collect();
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = builder.import_renamed(bdd, &mapping, &mut HashMap::new(), &mut HashMap::new());
+        return builder.export(root);
+    }
+
+    /// Compute a variable ordering which reduces the number of nodes needed to represent
+    /// all the given [bdds] (shared nodes are counted once). The result is a list of variable
+    /// names which can be used to create a new worker.
+    ///
+    /// The ordering is computed using sifting: every variable (starting with the ones which
+    /// appear in the most nodes) is moved through all levels by swapping it with its neighbours,
+    /// while the order of the remaining variables is fixed, and the best level is kept.
+    /// A swap only modifies the nodes at the two swapped levels, see [SiftingTable].
+    pub fn sift_order(&self, bdds: &[BDD]) -> Vec<String> {
+        let mut table = SiftingTable::new(self.num_vars, bdds);
+        table.sift_all();
+        return table.order.iter().map(|v| self.var_names[*v as usize].clone()).collect();
+    }
+
+}
+
+/// Moving a variable in one direction stops once the number of nodes exceeds
+/// `MAX_GROWTH_PERCENT` percent of the best size found so far.
+const MAX_GROWTH_PERCENT: usize = 120;
+
+/// Shared node table used by [BDDWorker::sift_order]. Unlike [BDD], nodes are not stored in
+/// a fixed order: when two adjacent levels are swapped, the nodes of the upper level are
+/// rewritten in place, so all references to them (including the roots) remain valid.
+/// Nodes are reference counted, hence the number of live nodes is always known without
+/// traversing the whole graph.
+struct SiftingTable {
+    // (var, low, high) for every node; 0 and 1 are the terminals with var = num_vars
+    nodes: Vec<(u32, usize, usize)>,
+    ref_count: Vec<usize>,
+    // indices of deleted nodes which can be reused
+    free: Vec<usize>,
+    // unique[var] maps (low, high) to the node of var with these links
+    unique: Vec<HashMap<(usize, usize), usize>>,
+    // order[level] is the variable at the given level, level[var] is its inverse
+    order: Vec<u32>,
+    level: Vec<usize>,
+    // number of live non-terminal nodes
+    size: usize
+}
+
+impl SiftingTable {
+
+    /// Create a table containing all the given [bdds] in the default variable ordering.
+    /// Every root keeps one reference, so the roots are never deleted.
+    fn new(num_vars: u32, bdds: &[BDD]) -> SiftingTable {
+        let mut table = SiftingTable {
+            nodes: vec![(num_vars, 0, 0), (num_vars, 1, 1)],
+            ref_count: vec![0, 0],
+            free: Vec::new(),
+            unique: vec![HashMap::new(); num_vars as usize],
+            order: (0..num_vars).collect(),
+            level: (0..(num_vars as usize)).collect(),
+            size: 0
+        };
+        for bdd in bdds {
+            let mut new_index: Vec<usize> = vec![0, 1];
+            for i in 2..bdd.size() {
+                let (low, high) = (new_index[bdd.low_link(i)], new_index[bdd.high_link(i)]);
+                table.acquire(low);
+                table.acquire(high);
+                new_index.push(table.mk_node(bdd.var(i) as u32, low, high));
+            }
+            // the root keeps its reference, the references of all other imported nodes are released
+            let root = new_index[bdd.last_index()];
+            table.acquire(root);
+            for node in new_index {
+                table.release(node);
+            }
+        }
+        return table;
+    }
+
+    fn acquire(&mut self, node: usize) {
+        if node > 1 { self.ref_count[node] += 1 }
+    }
+
+    /// Drop one reference to [node], deleting it (and releasing its links) once there are
+    /// no references left.
+    fn release(&mut self, node: usize) {
+        if node < 2 { return }
+        self.ref_count[node] -= 1;
+        if self.ref_count[node] == 0 {
+            let (var, low, high) = self.nodes[node];
+            self.unique[var as usize].remove(&(low, high));
+            self.free.push(node);
+            self.size -= 1;
+            self.release(low);
+            self.release(high);
+        }
+    }
+
+    /// Find or create a node with the given [var] and links. The references to [low] and
+    /// [high] held by the caller are consumed and the caller receives one reference to
+    /// the result.
+    fn mk_node(&mut self, var: u32, low: usize, high: usize) -> usize {
+        if low == high {
+            self.release(high);
+            return low;
+        }
+        if let Some(node) = self.unique[var as usize].get(&(low, high)) {
+            let node = *node;
+            self.ref_count[node] += 1;
+            self.release(low);
+            self.release(high);
+            return node;
+        }
+        let node = match self.free.pop() {
+            Some(node) => {
+                self.nodes[node] = (var, low, high);
+                self.ref_count[node] = 1;
+                node
+            }
+            None => {
+                self.nodes.push((var, low, high));
+                self.ref_count.push(1);
+                self.nodes.len() - 1
+            }
+        };
+        self.unique[var as usize].insert((low, high), node);
+        self.size += 1;
+        return node;
+    }
+
+    /// Swap the variables at levels [level] and `level + 1`.
+    fn swap(&mut self, level: usize) {
+        let x = self.order[level];
+        let y = self.order[level + 1];
+        let x_nodes: Vec<usize> = self.unique[x as usize].values().cloned().collect();
+        for node in x_nodes {
+            let (_, low, high) = self.nodes[node];
+            let (low_var, high_var) = (self.nodes[low].0, self.nodes[high].0);
+            // nodes which do not depend on y simply move one level down
+            if low_var != y && high_var != y { continue }
+            // cofactors of the node: f_xy
+            let (f00, f01) = if low_var == y { (self.nodes[low].1, self.nodes[low].2) } else { (low, low) };
+            let (f10, f11) = if high_var == y { (self.nodes[high].1, self.nodes[high].2) } else { (high, high) };
+            self.acquire(f00);
+            self.acquire(f10);
+            let new_low = self.mk_node(x, f00, f10);
+            self.acquire(f01);
+            self.acquire(f11);
+            let new_high = self.mk_node(x, f01, f11);
+            // the node now tests y first, which preserves its function and all references to it
+            self.unique[x as usize].remove(&(low, high));
+            self.nodes[node] = (y, new_low, new_high);
+            self.unique[y as usize].insert((new_low, new_high), node);
+            self.release(low);
+            self.release(high);
+        }
+        self.order.swap(level, level + 1);
+        self.level[x as usize] = level + 1;
+        self.level[y as usize] = level;
+    }
+
+    /// Sift all variables which are used by some node, starting with the largest levels.
+    fn sift_all(&mut self) {
+        let mut to_sift: Vec<u32> = (0..(self.order.len() as u32))
+            .filter(|v| !self.unique[*v as usize].is_empty())
+            .collect();
+        to_sift.sort_by_key(|v| std::cmp::Reverse(self.unique[*v as usize].len()));
+        for var in to_sift {
+            self.sift(var);
+        }
+    }
+
+    /// Move [var] through all levels and leave it at the level with the fewest nodes. The
+    /// variable first moves towards the closer end of the order.
+    fn sift(&mut self, var: u32) {
+        let last_level = self.order.len() - 1;
+        let mut best_size = self.size;
+        let mut best_level = self.level[var as usize];
+        let down_first = best_level > last_level / 2;
+        for down in [down_first, !down_first] {
+            loop {
+                let level = self.level[var as usize];
+                if down {
+                    if level == last_level { break }
+                    self.swap(level);
+                } else {
+                    if level == 0 { break }
+                    self.swap(level - 1);
+                }
+                if self.size < best_size {
+                    best_size = self.size;
+                    best_level = self.level[var as usize];
+                }
+                if self.size * 100 > best_size * MAX_GROWTH_PERCENT { break }
+            }
+        }
+        while self.level[var as usize] < best_level {
+            let level = self.level[var as usize];
+            self.swap(level);
+        }
+        while self.level[var as usize] > best_level {
+            let level = self.level[var as usize];
+            self.swap(level - 1);
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_worker(names: &[&str]) -> BDDWorker {
+        return BDDWorker::new(names.iter().map(|n| n.to_string()).collect());
+    }
+
+    const FORMULA: &str = "a1 & b1 | a2 & b2 | a3 & b3";
+
+    #[test]
+    fn bdd_transfer() {
+        let source = mk_worker(&["a1", "a2", "a3", "b1", "b2", "b3"]);
+        let target = mk_worker(&["b3", "a1", "b1", "x", "a2", "b2", "a3"]);
+        let bdd = source.eval_expression(FORMULA).unwrap();
+        let transferred = target.transfer(&bdd, &source);
+        assert_eq!(target.eval_expression(FORMULA).unwrap(), transferred);
+        assert_eq!(Ok(()), target.validate(&transferred));
+        assert_eq!(bdd, source.transfer(&transferred, &target));
+        assert!(target.is_true(&target.transfer(&source.mk_true(), &source)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_transfer_unknown_variable() {
+        let source = mk_worker(&["a", "b"]);
+        let target = mk_worker(&["a"]);
+        target.transfer(&source.mk_var(1), &source);
+    }
+
+    #[test]
+    fn bdd_sifting_table_swaps() {
+        // Swapping levels preserves all functions and the table size always matches
+        // the number of nodes of the BDDs in the resulting order.
+        let worker = mk_worker(&["a", "b", "c", "d", "e"]);
+        let bdds: Vec<BDD> = ["a & b | c & !d", "a ^ c ^ e", "(a | b) & (c | d) & (b | e)", "!a & d", "true"].iter()
+            .map(|f| worker.eval_expression(f).unwrap())
+            .collect();
+        let mut table = SiftingTable::new(5, &bdds);
+        for level in [0, 1, 3, 2, 0, 3, 1, 2, 0] {
+            table.swap(level);
+            let reordered = BDDWorker::new(table.order.iter().map(|v| worker.var_names[*v as usize].clone()).collect());
+            // the builder shares equal nodes, so it ends up with all distinct nodes of the BDDs
+            let mut builder = BDDBuilder::new(5);
+            for bdd in &bdds {
+                let transferred = reordered.transfer(bdd, &worker);
+                assert_eq!(*bdd, worker.transfer(&transferred, &reordered));
+                builder.import(&transferred);
+            }
+            assert_eq!(table.size + 2, builder.size());
+        }
+    }
+
+    #[test]
+    fn bdd_sift_order() {
+        // With all a-s before all b-s, the BDD is exponential in the number of pairs,
+        // while any order where a pair is kept together is linear.
+        let worker = mk_worker(&["a1", "a2", "a3", "b1", "b2", "b3"]);
+        let bdd = worker.eval_expression(FORMULA).unwrap();
+        assert_eq!(16, bdd.size());
+        let order = worker.sift_order(std::slice::from_ref(&bdd));
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(vec!["a1", "a2", "a3", "b1", "b2", "b3"], sorted);
+        let reordered = BDDWorker::new(order);
+        let transferred = reordered.transfer(&bdd, &worker);
+        assert_eq!(8, transferred.size());
+        assert_eq!(reordered.eval_expression(FORMULA).unwrap(), transferred);
+    }
+
+}
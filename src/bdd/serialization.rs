@@ -0,0 +1,260 @@
+//! Persistent formats of BDDs. Since a [BDD] is a pointer-free vector of nodes in post-order,
+//! both formats simply list the nodes (including the terminals) in their original order.
+//!
+//! The binary format starts with a header: magic bytes `BDD`, format version (one byte),
+//! number of variables (u32) and number of nodes (u64). Then every node is written as three
+//! u32 values: variable, low link and high link. All numbers are little endian.
+//!
+//! The text format starts with a line `BDD <version> <num_vars>`, followed by one line
+//! `<var> <low> <high>` per node.
+//!
+//! Readers check that the decoded vector is a valid BDD (see [BDDWorker::validate]), since
+//! the operations of [BDDWorker] are not guaranteed to work on malformed input.
+
+use super::{BDD, BDDNode, BDDWorker};
+use super::validation::validate_bdd;
+use std::fmt::{Display, Formatter, Error};
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 3] = b"BDD";
+const VERSION: u8 = 1;
+
+/// Error encountered while reading a serialized BDD.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BDDFormatError {
+    pub message: String
+}
+
+impl Display for BDDFormatError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BDDFormatError {}
+
+fn error<T>(message: String) -> Result<T, BDDFormatError> {
+    return Err(BDDFormatError { message });
+}
+
+/// Write given BDD to the output writer in the binary format.
+pub fn print_bdd_as_bytes(output: &mut dyn Write, bdd: &BDD) -> Result<(), std::io::Error> {
+    output.write_all(MAGIC)?;
+    output.write_all(&[VERSION])?;
+    output.write_all(&bdd.num_vars().to_le_bytes())?;
+    output.write_all(&(bdd.size() as u64).to_le_bytes())?;
+    for node in &bdd.0 {
+        output.write_all(&node.var.to_le_bytes())?;
+        output.write_all(&node.low.to_le_bytes())?;
+        output.write_all(&node.high.to_le_bytes())?;
+    }
+    return Ok(());
+}
+
+/// Write given BDD to the output writer in the text format.
+pub fn print_bdd_as_text(output: &mut dyn Write, bdd: &BDD) -> Result<(), std::io::Error> {
+    output.write_all(format!("BDD {} {}\n", VERSION, bdd.num_vars()).as_bytes())?;
+    for node in &bdd.0 {
+        output.write_all(format!("{} {} {}\n", node.var, node.low, node.high).as_bytes())?;
+    }
+    return Ok(());
+}
+
+fn read_u32(input: &mut dyn Read) -> Result<u32, BDDFormatError> {
+    let mut buffer = [0u8; 4];
+    return match input.read_exact(&mut buffer) {
+        Ok(()) => Ok(u32::from_le_bytes(buffer)),
+        Err(e) => error(format!("Unexpected end of BDD data: {}.", e))
+    }
+}
+
+/// Read a BDD in the binary format from the given input.
+pub fn read_bdd_from_bytes(input: &mut dyn Read) -> Result<BDD, BDDFormatError> {
+    let mut header = [0u8; 4];
+    if input.read_exact(&mut header).is_err() || &header[0..3] != MAGIC {
+        return error("Missing BDD header.".to_string());
+    }
+    if header[3] != VERSION {
+        return error(format!("Unsupported BDD format version {}.", header[3]));
+    }
+    let num_vars = read_u32(input)?;
+    let mut count = [0u8; 8];
+    if input.read_exact(&mut count).is_err() {
+        return error("Unexpected end of BDD data: missing node count.".to_string());
+    }
+    let count = u64::from_le_bytes(count);
+    if count > u64::from(u32::MAX) {
+        return error(format!("Too many BDD nodes ({}).", count));
+    }
+    let mut nodes: Vec<BDDNode> = Vec::new();
+    for _ in 0..count {
+        let var = read_u32(input)?;
+        let low = read_u32(input)?;
+        let high = read_u32(input)?;
+        nodes.push(BDDNode { var, low, high });
+    }
+    return check_bdd(BDD(nodes), num_vars);
+}
+
+/// Read a BDD in the text format from the given string.
+pub fn read_bdd_from_text(input: &str) -> Result<BDD, BDDFormatError> {
+    let mut lines = input.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let num_vars = match lines.next() {
+        None => return error("Missing BDD header.".to_string()),
+        Some((_, header)) => {
+            let header: Vec<&str> = header.split_whitespace().collect();
+            if header.len() != 3 || header[0] != "BDD" {
+                return error("Line 1: Expected header `BDD <version> <num_vars>`.".to_string());
+            }
+            if header[1] != VERSION.to_string() {
+                return error(format!("Line 1: Unsupported BDD format version {}.", header[1]));
+            }
+            match header[2].parse::<u32>() {
+                Ok(num_vars) => num_vars,
+                Err(_) => return error(format!("Line 1: Invalid number of variables `{}`.", header[2]))
+            }
+        }
+    };
+    let mut nodes: Vec<BDDNode> = Vec::new();
+    for (line_index, line) in lines {
+        let values: Vec<Option<u32>> = line.split_whitespace().map(|v| v.parse::<u32>().ok()).collect();
+        match values.as_slice() {
+            [Some(var), Some(low), Some(high)] => nodes.push(BDDNode { var: *var, low: *low, high: *high }),
+            _ => return error(format!("Line {}: Expected `<var> <low> <high>`, found `{}`.", line_index + 1, line.trim()))
+        }
+    }
+    return check_bdd(BDD(nodes), num_vars);
+}
+
+/// Check that the decoded [bdd] is valid, see [validate_bdd].
+fn check_bdd(bdd: BDD, num_vars: u32) -> Result<BDD, BDDFormatError> {
+    if bdd.0.is_empty() || bdd.num_vars() != num_vars {
+        return error(format!("Node 0 must be the zero terminal with {} variables.", num_vars));
+    }
+    return match validate_bdd(&bdd) {
+        Ok(()) => Ok(bdd),
+        Err(e) => error(format!("Invalid BDD: {}", e))
+    }
+}
+
+impl BDDWorker {
+
+    /// Convert the given BDD to the binary format.
+    pub fn as_bytes(&self, bdd: &BDD) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        print_bdd_as_bytes(&mut buffer, bdd)
+            .expect("Cannot write BDD to bytes.");
+        return buffer;
+    }
+
+    /// Read a BDD of this worker from the binary format.
+    pub fn from_bytes(&self, mut data: &[u8]) -> Result<BDD, BDDFormatError> {
+        return self.check_worker(read_bdd_from_bytes(&mut data)?);
+    }
+
+    /// Convert the given BDD to the text format.
+    pub fn as_text_string(&self, bdd: &BDD) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        print_bdd_as_text(&mut buffer, bdd)
+            .expect("Cannot write BDD to text string.");
+        return String::from_utf8(buffer)
+            .expect("Invalid UTF formatting in BDD text string.");
+    }
+
+    /// Read a BDD of this worker from the text format.
+    pub fn from_text_string(&self, data: &str) -> Result<BDD, BDDFormatError> {
+        return self.check_worker(read_bdd_from_text(data)?);
+    }
+
+    fn check_worker(&self, bdd: BDD) -> Result<BDD, BDDFormatError> {
+        return match self.validate(&bdd) {
+            Ok(()) => Ok(bdd),
+            Err(e) => error(format!("Invalid BDD: {}", e))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_worker() -> BDDWorker {
+        return BDDWorker::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+    }
+
+    fn mk_test_bdds(worker: &BDDWorker) -> Vec<BDD> {
+        return vec![
+            worker.mk_false(),
+            worker.mk_true(),
+            worker.mk_var(1),
+            worker.eval_expression("a & !(b | c)").unwrap(),
+            worker.eval_expression("a ^ b ^ c").unwrap()
+        ];
+    }
+
+    #[test]
+    fn bdd_bytes_round_trip() {
+        let worker = mk_worker();
+        for bdd in mk_test_bdds(&worker) {
+            let bytes = worker.as_bytes(&bdd);
+            assert_eq!(16 + 12 * bdd.size(), bytes.len());
+            assert_eq!(bdd, worker.from_bytes(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn bdd_text_round_trip() {
+        let worker = mk_worker();
+        for bdd in mk_test_bdds(&worker) {
+            assert_eq!(bdd, worker.from_text_string(&worker.as_text_string(&bdd)).unwrap());
+        }
+    }
+
+    #[test]
+    fn bdd_text_format() {
+        let worker = mk_worker();
+        let expected = "BDD 1 3\n3 0 0\n3 1 1\n1 0 1\n";
+        assert_eq!(expected, worker.as_text_string(&worker.mk_var(1)));
+    }
+
+    #[test]
+    fn bdd_bytes_invalid() {
+        let worker = mk_worker();
+        let bytes = worker.as_bytes(&worker.mk_var(1));
+        // truncated data
+        assert!(worker.from_bytes(&bytes[0..(bytes.len() - 1)]).is_err());
+        assert!(worker.from_bytes(&bytes[0..2]).is_err());
+        // wrong version
+        let mut wrong_version = bytes.clone();
+        wrong_version[3] = 2;
+        assert!(worker.from_bytes(&wrong_version).is_err());
+        // different number of variables
+        assert!(BDDWorker::new_anonymous(4).from_bytes(&bytes).is_err());
+        // variable of the last node is out of bounds
+        let mut wrong_var = bytes.clone();
+        wrong_var[40] = 7;
+        assert!(worker.from_bytes(&wrong_var).is_err());
+    }
+
+    #[test]
+    fn bdd_text_invalid() {
+        let worker = mk_worker();
+        assert!(worker.from_text_string("").is_err());
+        assert!(worker.from_text_string("BDD 2 3\n3 0 0\n").is_err());
+        assert!(worker.from_text_string("BDD 1 3\n3 0 0\n3 1\n").is_err());
+        // missing terminals
+        assert!(worker.from_text_string("BDD 1 3\n").is_err());
+        assert!(worker.from_text_string("BDD 1 3\n3 1 1\n").is_err());
+        // link to a node defined later
+        assert!(worker.from_text_string("BDD 1 3\n3 0 0\n3 1 1\n1 0 3\n2 0 1\n").is_err());
+        // variable ordering violated
+        assert!(worker.from_text_string("BDD 1 3\n3 0 0\n3 1 1\n1 0 1\n1 0 2\n").is_err());
+        // duplicate node
+        assert!(worker.from_text_string("BDD 1 3\n3 0 0\n3 1 1\n1 0 1\n1 0 1\n0 2 3\n").is_err());
+        let e = worker.from_text_string("BDD 1 3\n3 0 0\n3 1 1\nx 0 1\n").unwrap_err();
+        assert_eq!("Line 4: Expected `<var> <low> <high>`, found `x 0 1`.", e.to_string());
+        assert_eq!(worker.mk_false(), worker.from_text_string("BDD 1 3\n3 0 0\n").unwrap());
+    }
+
+}
@@ -0,0 +1,183 @@
+use super::{BDD, BDDWorker};
+use super::builder::BDDBuilder;
+use std::collections::HashMap;
+
+/// Restriction fixes the value of some variables in a BDD (i.e. computes a cofactor
+/// of the function), while composition substitutes a variable with a whole function.
+impl BDDWorker {
+
+    /// Create a BDD corresponding to `bdd[var/value]`, i.e. the positive or negative
+    /// cofactor of the function with respect to the given variable.
+    pub fn restrict(&self, bdd: &BDD, var: u32, value: bool) -> BDD {
+        return self.restrict_cube(bdd, &[(var, value)]);
+    }
+
+    /// Create a BDD where all variables of the given [cube] are fixed to their respective
+    /// values. Panics if some variable does not exist or is fixed to two different values.
+    pub fn restrict_cube(&self, bdd: &BDD, cube: &[(u32, bool)]) -> BDD {
+        let values = self.cube_values(cube);
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = builder.import(bdd);
+        let result = restrict(&mut builder, root, &values, &mut HashMap::new());
+        return builder.export(result);
+    }
+
+    /// Create a BDD corresponding to `bdd[var/replacement]`, i.e. every occurrence of the
+    /// variable is substituted with the function given by [replacement]. This is computed
+    /// using the Shannon expansion `(replacement & bdd[var/1]) | (!replacement & bdd[var/0])`.
+    pub fn compose(&self, bdd: &BDD, var: u32, replacement: &BDD) -> BDD {
+        let positive = self.mk_and(replacement, &self.restrict(bdd, var, true));
+        let negative = self.mk_and(&self.mk_not(replacement), &self.restrict(bdd, var, false));
+        return self.mk_or(&positive, &negative);
+    }
+
+    /// Create a BDD corresponding to `bdd[var/!var]`, i.e. the value of [var] is negated in
+    /// every satisfying valuation. This only swaps the links of nodes conditioning on [var].
+    pub fn flip_var(&self, bdd: &BDD, var: u32) -> BDD {
+        if var >= self.num_vars { self.var_index_out_of_bounds(var) }
+        let mut builder = BDDBuilder::new(self.num_vars);
+        let root = builder.import(bdd);
+        let result = flip_var(&mut builder, root, var, &mut HashMap::new());
+        return builder.export(result);
+    }
+
+    /// Create a vector where `values[i]` is the value of variable `i` in the given [cube].
+    /// Panics if some variable does not exist or is fixed to two different values.
+    pub(super) fn cube_values(&self, cube: &[(u32, bool)]) -> Vec<Option<bool>> {
+        let mut values: Vec<Option<bool>> = vec![None; self.num_vars as usize];
+        for (var, value) in cube {
+            if *var >= self.num_vars { self.var_index_out_of_bounds(*var) }
+            match values[*var as usize] {
+                Some(old_value) if old_value != *value => {
+                    panic!("Variable {} is restricted to both true and false.", var)
+                }
+                _ => values[*var as usize] = Some(*value)
+            }
+        }
+        return values;
+    }
+
+}
+
+/// Restrict the sub-graph of the given [node], skipping all nodes whose variable is fixed
+/// in [values] and replacing them with the respective child.
+pub(super) fn restrict(
+    builder: &mut BDDBuilder,
+    node: usize,
+    values: &[Option<bool>],
+    finished: &mut HashMap<usize, usize>
+) -> usize {
+    if node <= 1 { return node }
+    if let Some(result) = finished.get(&node) { return *result }
+    let var = builder.var(node);
+    let result = match values[var as usize] {
+        Some(true) => restrict(builder, builder.high(node), values, finished),
+        Some(false) => restrict(builder, builder.low(node), values, finished),
+        None => {
+            let low = restrict(builder, builder.low(node), values, finished);
+            let high = restrict(builder, builder.high(node), values, finished);
+            builder.mk_node(var, low, high)
+        }
+    };
+    finished.insert(node, result);
+    return result;
+}
+
+/// Negate [var] in the sub-graph of the given [node] by swapping the links of all nodes
+/// conditioning on [var]. Nodes below [var] are not affected and are returned as they are.
+pub(super) fn flip_var(
+    builder: &mut BDDBuilder,
+    node: usize,
+    var: u32,
+    finished: &mut HashMap<usize, usize>
+) -> usize {
+    if builder.var(node) > var { return node }
+    if let Some(result) = finished.get(&node) { return *result }
+    let (low, high) = (builder.low(node), builder.high(node));
+    let result = if builder.var(node) == var {
+        builder.mk_node(var, high, low)
+    } else {
+        let new_low = flip_var(builder, low, var, finished);
+        let new_high = flip_var(builder, high, var, finished);
+        builder.mk_node(builder.var(node), new_low, new_high)
+    };
+    finished.insert(node, result);
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bdd::tests::mk_test_bdd;
+
+    #[test]
+    fn bdd_restrict_single_variable() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        assert_eq!(worker.mk_var(1), worker.restrict(&bdd, 0, true));
+        assert_eq!(worker.mk_var(2), worker.restrict(&bdd, 0, false));
+        let expected = worker.mk_or(&worker.mk_var(0), &worker.mk_and(&worker.mk_not(&worker.mk_var(0)), &worker.mk_var(2)));
+        assert_eq!(expected, worker.restrict(&bdd, 1, true));
+        assert_eq!(bdd, worker.restrict(&bdd, 3, false));
+        assert!(worker.is_true(&worker.restrict(&worker.mk_true(), 0, false)));
+    }
+
+    #[test]
+    fn bdd_restrict_cube() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        assert!(worker.is_true(&worker.restrict_cube(&bdd, &[(0, true), (1, true)])));
+        assert!(worker.is_false(&worker.restrict_cube(&bdd, &[(0, false), (2, false)])));
+        assert_eq!(worker.mk_var(0), worker.restrict_cube(&bdd, &[(1, true), (2, false)]));
+        // restriction is the same as a sequence of single variable restrictions
+        let sequential = worker.restrict(&worker.restrict(&bdd, 2, true), 1, false);
+        assert_eq!(sequential, worker.restrict_cube(&bdd, &[(2, true), (1, false), (2, true)]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_restrict_cube_inconsistent() {
+        let worker = BDDWorker::new_anonymous(4);
+        worker.restrict_cube(&worker.mk_var(0), &[(0, true), (0, false)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_restrict_unknown_variable() {
+        let worker = BDDWorker::new_anonymous(2);
+        worker.restrict(&worker.mk_var(0), 2, true);
+    }
+
+    #[test]
+    fn bdd_compose() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        // substituting x0 := x3 gives (x3 & x1) | (!x3 & x2)
+        let x3 = worker.mk_var(3);
+        let expected = worker.mk_or(
+            &worker.mk_and(&x3, &worker.mk_var(1)),
+            &worker.mk_and(&worker.mk_not(&x3), &worker.mk_var(2))
+        );
+        assert_eq!(expected, worker.compose(&bdd, 0, &x3));
+        // substituting a constant is a restriction
+        assert_eq!(worker.restrict(&bdd, 0, true), worker.compose(&bdd, 0, &worker.mk_true()));
+        // substituting x1 := x0 gives x0 | (!x0 & x2) = x0 | x2
+        let expected = worker.mk_or(&worker.mk_var(0), &worker.mk_var(2));
+        assert_eq!(expected, worker.compose(&bdd, 1, &worker.mk_var(0)));
+        // substituting a variable by itself does nothing
+        assert_eq!(bdd, worker.compose(&bdd, 2, &worker.mk_var(2)));
+    }
+
+    #[test]
+    fn bdd_flip_var() {
+        let worker = BDDWorker::new_anonymous(4);
+        let bdd = mk_test_bdd(&worker);
+        for var in 0..4 {
+            let expected = worker.compose(&bdd, var, &worker.mk_not(&worker.mk_var(var)));
+            assert_eq!(expected, worker.flip_var(&bdd, var));
+        }
+        assert!(worker.is_true(&worker.flip_var(&worker.mk_true(), 1)));
+        assert!(worker.is_false(&worker.flip_var(&worker.mk_false(), 1)));
+    }
+
+}
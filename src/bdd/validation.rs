@@ -0,0 +1,174 @@
+use super::{BDD, BDDNode, BDDWorker};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Error};
+
+/// Describes why a vector of nodes is not a valid [BDD].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BDDValidationError {
+    pub message: String
+}
+
+impl Display for BDDValidationError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BDDValidationError {}
+
+fn error<T>(message: String) -> Result<T, BDDValidationError> {
+    return Err(BDDValidationError { message });
+}
+
+impl BDDWorker {
+
+    /// Check that the given [bdd] satisfies all invariants expected by the operations
+    /// of this worker, see [validate_bdd]. Additionally, the BDD must use the same number
+    /// of variables as this worker.
+    pub fn validate(&self, bdd: &BDD) -> Result<(), BDDValidationError> {
+        validate_bdd(bdd)?;
+        if bdd.num_vars() != self.num_vars {
+            return error(format!("BDD has {} variables, but worker has {} variables.", bdd.num_vars(), self.num_vars));
+        }
+        return Ok(());
+    }
+
+}
+
+/// Check the structural invariants of a canonical [bdd]:
+///  - node 0 is the `zero` terminal and (unless the BDD is `false`) node 1 is the `one` terminal,
+///    both labeled with the number of variables;
+///  - all other nodes use existing variables and link only to nodes at lower indices;
+///  - variables strictly increase along every edge;
+///  - there are no duplicate nodes and no redundant nodes (with equal links);
+///  - every node is reachable from the root, which is the last node;
+///  - nodes are in the canonical order produced by the BDD operations (DFS post-order from
+///    the root, visiting the high link first), which is what makes equal functions
+///    result in equal BDDs.
+pub(super) fn validate_bdd(bdd: &BDD) -> Result<(), BDDValidationError> {
+    if bdd.0.is_empty() {
+        return error("BDD must contain at least the zero terminal.".to_string());
+    }
+    let num_vars = bdd.num_vars();
+    if bdd.0[0] != BDDNode::mk_zero(num_vars) {
+        return error(format!("Node 0 must be the zero terminal, found {:?}.", bdd.0[0]));
+    }
+    if bdd.size() > 1 && bdd.0[1] != BDDNode::mk_one(num_vars) {
+        return error(format!("Node 1 must be the one terminal, found {:?}.", bdd.0[1]));
+    }
+    let mut unique: HashSet<BDDNode> = HashSet::with_capacity(bdd.size());
+    for i in 2..bdd.size() {
+        let node = bdd.0[i];
+        if node.var >= num_vars {
+            return error(format!("Node {} uses variable {}, but there are only {} variables.", i, node.var, num_vars));
+        }
+        if node.low as usize >= i || node.high as usize >= i {
+            return error(format!("Node {} links to a node which is not defined before it.", i));
+        }
+        if node.var >= bdd.0[node.low as usize].var || node.var >= bdd.0[node.high as usize].var {
+            return error(format!("Node {} violates the variable ordering.", i));
+        }
+        if node.low == node.high {
+            return error(format!("Node {} is redundant, both links point to node {}.", i, node.low));
+        }
+        if !unique.insert(node) {
+            return error(format!("Node {} is a duplicate of another node.", i));
+        }
+    }
+    // Since links only go to lower indices, one backward pass marks all reachable nodes.
+    let mut is_reachable = vec![false; bdd.size()];
+    is_reachable[bdd.last_index()] = true;
+    for i in (2..bdd.size()).rev() {
+        if !is_reachable[i] {
+            return error(format!("Node {} is not reachable from the root.", i));
+        }
+        is_reachable[bdd.low_link(i)] = true;
+        is_reachable[bdd.high_link(i)] = true;
+    }
+    // Replay the traversal of the canonical export and check that every node is placed
+    // at the index where the export would put it.
+    let mut is_placed = vec![false; bdd.size()];
+    is_placed[0] = true;
+    if bdd.size() > 1 { is_placed[1] = true }
+    let mut next_index = 2;
+    let mut stack: Vec<usize> = vec![bdd.last_index()];
+    while let Some(node) = stack.last() {
+        let node = *node;
+        if is_placed[node] {
+            stack.pop();
+            continue;
+        }
+        let (low, high) = (bdd.low_link(node), bdd.high_link(node));
+        match (is_placed[low], is_placed[high]) {
+            (true, true) => {
+                if node != next_index {
+                    return error(format!("Node {} is not in canonical order, expected it at index {}.", node, next_index));
+                }
+                is_placed[node] = true;
+                next_index += 1;
+                stack.pop();
+            }
+            (false, true) => stack.push(low),
+            (true, false) => stack.push(high),
+            (false, false) => {
+                stack.push(low);
+                stack.push(high);
+            }
+        }
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mk_bdd(nodes: &[(u32, u32, u32)]) -> BDD {
+        return BDD(nodes.iter().map(|(var, low, high)| BDDNode { var: *var, low: *low, high: *high }).collect());
+    }
+
+    #[test]
+    fn bdd_validate_valid() {
+        let worker = BDDWorker::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        for formula in &["true", "false", "a", "!b", "a & !(b | c)", "a ^ b ^ c", "a => c"] {
+            assert_eq!(Ok(()), worker.validate(&worker.eval_expression(formula).unwrap()));
+        }
+        let bdd = worker.eval_expression("a | c").unwrap();
+        let other = worker.eval_expression("a & b | !a & c").unwrap();
+        assert_eq!(Ok(()), worker.validate(&worker.mk_and(&bdd, &other)));
+        assert_eq!(Ok(()), worker.validate(&worker.mk_or(&bdd, &other)));
+        assert_eq!(Ok(()), worker.validate(&worker.mk_equals(&bdd, &other)));
+        assert_eq!(Ok(()), worker.validate(&worker.exists(&bdd, &[0])));
+        assert_eq!(Ok(()), worker.validate(&worker.rename_variables(&bdd, &[2, 1, 0])));
+    }
+
+    #[test]
+    fn bdd_validate_invalid() {
+        let worker = BDDWorker::new_anonymous(3);
+        let invalid = vec![
+            vec![],                                             // empty
+            vec![(2, 0, 0)],                                    // wrong zero terminal
+            vec![(3, 0, 0), (3, 0, 0)],                         // wrong one terminal
+            vec![(3, 0, 0), (3, 1, 1), (3, 0, 1)],              // variable out of bounds
+            vec![(3, 0, 0), (3, 1, 1), (1, 0, 2)],              // link to itself
+            vec![(3, 0, 0), (3, 1, 1), (1, 0, 1), (2, 0, 2)],   // variable ordering
+            vec![(3, 0, 0), (3, 1, 1), (1, 1, 1)],              // redundant node
+            vec![(3, 0, 0), (3, 1, 1), (1, 0, 1), (1, 0, 1), (0, 2, 3)], // duplicate node
+            vec![(3, 0, 0), (3, 1, 1), (1, 0, 1), (2, 0, 1)],   // unreachable node
+            vec![(3, 0, 0), (3, 1, 1), (2, 0, 1), (1, 0, 1), (0, 2, 3)], // non-canonical order
+        ];
+        for nodes in invalid {
+            assert!(worker.validate(&mk_bdd(&nodes)).is_err(), "{:?} should be invalid.", nodes);
+        }
+        // the same function as the non-canonical BDD above is valid when ordered canonically
+        let canonical = mk_bdd(&[(3, 0, 0), (3, 1, 1), (1, 0, 1), (2, 0, 1), (0, 3, 2)]);
+        assert_eq!(Ok(()), worker.validate(&canonical));
+        let x0 = worker.mk_var(0);
+        let expected = worker.mk_or(&worker.mk_and(&x0, &worker.mk_var(1)), &worker.mk_and(&worker.mk_not(&x0), &worker.mk_var(2)));
+        assert_eq!(expected, canonical);
+        // valid, but with a different number of variables
+        let e = worker.validate(&BDDWorker::new_anonymous(4).mk_var(0)).unwrap_err();
+        assert_eq!("BDD has 4 variables, but worker has 3 variables.", e.to_string());
+    }
+
+}
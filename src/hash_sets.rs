@@ -0,0 +1,135 @@
+use rand::prelude::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cmp::min;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Disjoint sets (union-find) over a sparse set of integer keys. Similar to
+/// [crate::u32::sequential::DisjointSets], every set carries one extra u32 payload and
+/// upon union, the minimum of the two payloads is kept.
+///
+/// Keys are only stored once they are touched, hence the memory footprint depends on the
+/// number of visited elements, not on the size of the key space. Keys which have not been
+/// touched yet are considered to be singleton sets with payload given by `default_payload`.
+pub struct HashDisjointSets<K> {
+    hash_mask: u64,
+    default_payload: u32,
+    nodes: HashMap<K, Node<K>>
+}
+
+enum Node<K> {
+    Root(u32),
+    Child(K)
+}
+
+impl <K> HashDisjointSets<K> where K: Copy + Eq + Hash + Into<u64> {
+
+    /// Create a new empty structure with the given [seed] for key hashing.
+    pub fn new(default_payload: u32, seed: u64) -> HashDisjointSets<K> {
+        let mut rnd = StdRng::seed_from_u64(seed);
+        return HashDisjointSets {
+            hash_mask: rnd.next_u64(),
+            default_payload,
+            nodes: HashMap::new()
+        }
+    }
+
+    /// Number of touched keys.
+    pub fn len(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.nodes.is_empty();
+    }
+
+    /// Check whether the [key] has been touched by this structure.
+    pub fn contains(&self, key: K) -> bool {
+        return self.nodes.contains_key(&key);
+    }
+
+    /// Iterate over all touched keys (in no particular order).
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        return self.nodes.keys().cloned();
+    }
+
+    /// Compute the representing key of the set given by [key]. During search,
+    /// every non-trivial path is contracted by path halving.
+    pub fn find_root(&mut self, key: K) -> K {
+        let mut item = key;
+        while let Some(Node::Child(parent)) = self.nodes.get(&item) {
+            let parent = *parent;
+            match self.nodes.get(&parent) {
+                Some(Node::Child(parents_parent)) => {
+                    let parents_parent = *parents_parent;
+                    self.nodes.insert(item, Node::Child(parents_parent));
+                    item = parents_parent;
+                },
+                _ => return parent
+            }
+        }
+        return item;
+    }
+
+    /// Get the u32 payload of the given set.
+    pub fn get_payload(&mut self, key: K) -> u32 {
+        let root = self.find_root(key);
+        return match self.nodes.get(&root) {
+            Some(Node::Root(payload)) => *payload,
+            _ => self.default_payload
+        }
+    }
+
+    /// Set the u32 payload for the given set.
+    pub fn set_payload(&mut self, key: K, payload: u32) {
+        let root = self.find_root(key);
+        self.nodes.insert(root, Node::Root(payload));
+    }
+
+    /// Union two sets.
+    pub fn union(&mut self, left: K, right: K) {
+        let root_left = self.find_root(left);
+        let root_right = self.find_root(right);
+        if root_left != root_right {
+            let new_payload = min(self.get_payload(root_left), self.get_payload(root_right));
+            if (root_left.into() ^ self.hash_mask) > (root_right.into() ^ self.hash_mask) {
+                // attach right under left because left is "bigger"
+                self.nodes.insert(root_right, Node::Child(root_left));
+                self.nodes.insert(root_left, Node::Root(new_payload));
+            } else {
+                // attach left under right because right is "bigger"
+                self.nodes.insert(root_left, Node::Child(root_right));
+                self.nodes.insert(root_right, Node::Root(new_payload));
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_sets_union_and_payload() {
+        let mut sets: HashDisjointSets<u64> = HashDisjointSets::new(7, 1234567890);
+        assert!(sets.is_empty());
+        assert_eq!(7, sets.get_payload(1 << 40));
+        assert!(!sets.contains(1 << 40));
+        sets.set_payload(1, 5);
+        sets.set_payload(2, 3);
+        sets.set_payload(3, 9);
+        sets.union(1, 2);
+        sets.union(3, 1 << 40);
+        assert_eq!(sets.find_root(1), sets.find_root(2));
+        assert_eq!(sets.find_root(3), sets.find_root(1 << 40));
+        assert_ne!(sets.find_root(1), sets.find_root(3));
+        assert_eq!(3, sets.get_payload(1));
+        assert_eq!(7, sets.get_payload(3));
+        sets.union(2, 3);
+        assert_eq!(sets.find_root(1), sets.find_root(1 << 40));
+        assert_eq!(3, sets.get_payload(1 << 40));
+        assert_eq!(4, sets.len());
+    }
+
+}
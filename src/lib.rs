@@ -1,5 +1,9 @@
 pub mod bitset;
 
+pub mod hash_sets;
+
 pub mod u32;
 
-pub mod bdd;
\ No newline at end of file
+pub mod u64;
+
+pub mod bdd;
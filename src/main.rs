@@ -7,17 +7,14 @@ fn main() {
     //let mut builder = BNBuilder::new();
     /*let b = builder.make_variable("B");
     let a = builder.make_variable("A");
-    builder.update_function(&a, Box::new(move |s| {
-        !s.get(&a) || s.get(&b)
-    }));
-    builder.update_function(&b, Box::new(move |s| {
-        s.get(&a) || s.get(&b)
-    }));*/
+    builder.update_function(&a, !var(a) | var(b));
+    builder.update_function(&b, var(a) | var(b));*/
 
     let network = t2dm_model();//builder.build_network();
 
     let _args: Vec<String> = std::env::args().collect();
     //parallel_scc(&network, args[1].parse().unwrap());
-    scc_alt(&network);
+    let decomposition = scc_alt(&network);
+    println!("Non-trivial components: {}", decomposition.non_trivial_count());
 }
 
@@ -1,30 +1,34 @@
 use std::ops::{BitOr, BitXor};
 use std::collections::HashMap;
-use std::fmt::{Display, Formatter, Error};
+use std::fmt::{Display, Formatter, Error, Debug};
+use std::hash::Hash;
+use crate::u32::expression::{BooleanExpression, CompiledExpression};
 
 /// Every state ID is internally stored as u32 and represents a binary encoding
 /// of the boolean vector of network variables.
 ///
 /// The ordering of bits corresponds to the ordering of variables in the network,
-/// i.e. variable with ID 0 is stored in the least significant bit of the state integer.
-/// Note that this results in a counter-intuitive behaviour when state printed as binary
-/// string is reversed compared to what one might expect due to the implicit endianness
-/// when printing numbers, i.e. state {0,1,0,1,1} is printed as 0b11010.
+/// i.e. variable with ID `i` is stored in the bit `i` of the state integer (`value >> i & 1`),
+/// so variable 0 is the least significant bit. For example, the state where variables
+/// `0..5` have values {0,1,0,1,1} has value `0b11010`. The same convention is used when
+/// converting states to and from other representations (bit sets indexed by the state
+/// value, or the symbolic sets of [crate::u32::symbolic::SymbolicAsyncGraph]).
 ///
-/// This also means that any printing or exporting needs to be performed using the
-/// [BooleanNetwork] object, because the state has no information about the actual number
-/// of variables it stores.
+/// Displaying a state only prints its value, e.g. `State(26)`, because the state has
+/// no information about the actual number of variables or their names. Human-readable
+/// output needs to be performed using the [BooleanNetwork] object
+/// (see [AsyncNetwork::format_state]).
 ///
 /// StateId overrides bit-or operator (|) for extracting values of specific variables
 /// and bit-xor operator (^) for flipping values of specific variables.
-#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone, Ord, PartialOrd)]
 pub struct StateId { pub value: u32 }
 
 /// A variable ID represents an index into the variable array of the [BooleanNetwork].
 /// Note that you should not store VariableIds extensively as they are rather
 /// memory inefficient (value is very small, yet it occupies 32 bits). If you
 /// need a lot of variable iterators with small memory footprint, look at [VariableIterator].
-#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone, Ord, PartialOrd)]
 pub struct VariableId { value: u32 }
 
 /// Variable iterator is a simple, memory-efficient iterator over variables of
@@ -46,15 +50,58 @@ pub struct StateIterator {
 /// Boolean network is a type of simple model with boolean variables and asynchronous update
 /// functions.
 pub struct BooleanNetwork {
-    update_functions: Vec<Box<dyn Fn(StateId) -> bool + Sync>>
+    variable_names: Vec<String>,
+    update_functions: Vec<BooleanExpression>,
+    compiled_functions: Vec<CompiledExpression>
 }
 
 /// Boolean network builder allows to create instances of [BooleanNetwork] in a relatively
 /// safe fashion. Specifically, it check for duplicities and missing values.
 pub struct BooleanNetworkBuilder {
+    variable_limit: u32,
     variable_count: u32,
     variable_names: HashMap<VariableId, String>,
-    update_functions: HashMap<VariableId, Box<dyn Fn(StateId) -> bool + Sync>>
+    update_functions: HashMap<VariableId, BooleanExpression>
+}
+
+/// Asynchronous boolean network with a specific width of states. This is implemented by
+/// [BooleanNetwork] as well as by [crate::u64::bn::BooleanNetwork], so that algorithms
+/// which do not need to enumerate the whole state space work with both.
+pub trait AsyncNetwork {
+    /// State of the network. Converting the state to u64 gives its binary encoding,
+    /// as described in [StateId].
+    type State: Copy + Eq + Ord + Hash + Debug + Into<u64>;
+
+    fn var_count(&self) -> u8;
+
+    /// The name of the given [variable].
+    fn get_variable_name(&self, variable: &VariableId) -> &String;
+
+    /// Check if [state] has a successor in dimension given by [variable]. If yes,
+    /// return such successor, otherwise return [None].
+    fn successor(&self, state: &Self::State, variable: &VariableId) -> Option<Self::State>;
+
+    fn variables(&self) -> VariableIterator {
+        return VariableIterator::new(self.var_count());
+    }
+
+    /// Find a variable with the given [name], if it exists.
+    fn find_variable(&self, name: &str) -> Option<VariableId> {
+        return (0..(self.var_count() as usize))
+            .map(VariableId::from_index)
+            .find(|v| self.get_variable_name(v) == name);
+    }
+
+    /// Format the given [state] as a list of variable values, e.g. `A=1 B=0 C=1`.
+    /// Variables are listed in the order of their creation.
+    fn format_state(&self, state: &Self::State) -> String {
+        let value: u64 = (*state).into();
+        return (0..(self.var_count() as usize))
+            .map(|index| format!("{}={}", self.get_variable_name(&VariableId::from_index(index)), (value >> index) & 1))
+            .collect::<Vec<String>>()
+            .join(" ");
+    }
+
 }
 
 impl BitOr<VariableId> for StateId {
@@ -83,7 +130,20 @@ impl Display for VariableId {
 
 impl Display for StateId {
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
-        f.write_str(&format!("{:#032b}", self.value).chars().rev().collect::<String>())
+        f.write_fmt(format_args!("State({})", self.value))
+    }
+}
+
+/// Boolean network is displayed as a list of update functions, one per line,
+/// in the form `name := function`.
+impl Display for BooleanNetwork {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let mut variables: Vec<VariableId> = self.variables().collect();
+        variables.sort();
+        for v in variables {
+            f.write_fmt(format_args!("{} := {}\n", self.get_variable_name(&v), self.get_update_function(&v).format(self)))?;
+        }
+        return Ok(());
     }
 }
 
@@ -118,6 +178,16 @@ impl Iterator for StateIterator {
 
 impl BooleanNetwork {
 
+    /// The name of the given [variable].
+    pub fn get_variable_name(&self, variable: &VariableId) -> &String {
+        return &self.variable_names[variable.value as usize];
+    }
+
+    /// The update function of the given [variable].
+    pub fn get_update_function(&self, variable: &VariableId) -> &BooleanExpression {
+        return &self.update_functions[variable.value as usize];
+    }
+
     pub fn var_count(&self) -> u8 {
         return self.update_functions.len() as u8;
     }
@@ -128,7 +198,7 @@ impl BooleanNetwork {
     }
 
     pub fn variables(&self) -> VariableIterator {
-        return VariableIterator { remaining: self.var_count() }
+        return VariableIterator::new(self.var_count());
     }
 
     pub fn states(&self) -> StateIterator {
@@ -139,17 +209,80 @@ impl BooleanNetwork {
     /// Check if [state] has a successor in dimension given by [variable]. If yes,
     /// return such successor, otherwise return [None].
     pub fn successor(&self, state: &StateId, variable: &VariableId) -> Option<StateId> {
-        let target: bool = self.update_functions[variable.value as usize](state.clone());
+        let target: bool = self.evaluate(*state, variable);
         return if *state | *variable == target { None } else { Some(*state ^ *variable) }
     }
 
 }
 
+impl AsyncNetwork for BooleanNetwork {
+    type State = StateId;
+
+    fn var_count(&self) -> u8 {
+        return BooleanNetwork::var_count(self);
+    }
+
+    fn get_variable_name(&self, variable: &VariableId) -> &String {
+        return BooleanNetwork::get_variable_name(self, variable);
+    }
+
+    fn successor(&self, state: &StateId, variable: &VariableId) -> Option<StateId> {
+        return BooleanNetwork::successor(self, state, variable);
+    }
+
+}
+
+impl From<StateId> for u64 {
+    fn from(state: StateId) -> Self {
+        return state.value as u64;
+    }
+}
+
+impl BooleanNetwork {
+
+    /// Evaluate the update function of [variable] in the given [state].
+    fn evaluate(&self, state: StateId, variable: &VariableId) -> bool {
+        let index = variable.value as usize;
+        return self.compiled_functions[index].evaluate(&self.update_functions[index], |v| state | v);
+    }
+
+}
+
+impl VariableId {
+
+    /// Index of this variable in the network (variables are indexed in the order of creation).
+    pub fn index(&self) -> usize {
+        return self.value as usize;
+    }
+
+    pub(crate) fn from_index(index: usize) -> VariableId {
+        return VariableId { value: index as u32 }
+    }
+
+}
+
+impl VariableIterator {
+
+    /// Make an iterator over the first [count] variables.
+    pub(crate) fn new(count: u8) -> VariableIterator {
+        return VariableIterator { remaining: count }
+    }
+
+}
+
 impl BooleanNetworkBuilder {
 
     /// Make a new empty boolean network builder.
     pub fn new() -> BooleanNetworkBuilder {
+        return BooleanNetworkBuilder::with_variable_limit(32);
+    }
+
+    /// Make a new builder which allows networks with up to [limit] variables. The built
+    /// network still has to fit into 32 bits, but the builder can be also used to assemble
+    /// parts of wider networks (see [BooleanNetworkBuilder::into_parts]).
+    pub(crate) fn with_variable_limit(limit: u32) -> BooleanNetworkBuilder {
         return BooleanNetworkBuilder {
+            variable_limit: limit,
             variable_count: 0,
             variable_names: HashMap::new(),
             update_functions: HashMap::new()
@@ -159,7 +292,9 @@ impl BooleanNetworkBuilder {
     /// Create a new variable in this network.
     /// Panics if the variable already exists or the network is too large.
     pub fn make_variable(&mut self, name: &str) -> VariableId {
-        if self.variable_count == 32 { panic!("Cannot create network with more than 32 variables."); }
+        if self.variable_count == self.variable_limit {
+            panic!("Cannot create network with more than {} variables.", self.variable_limit);
+        }
         let variable = VariableId { value: self.variable_count };
         self.variable_count += 1;
         for (_, existing) in &self.variable_names {
@@ -170,11 +305,17 @@ impl BooleanNetworkBuilder {
     }
 
     /// Associate an update function with a variable.
-    /// Panics if the variable does not exist or if it already has a function defined.
-    pub fn update_function(&mut self, variable: &VariableId, function: Box<dyn Fn(StateId) -> bool + Sync>) {
+    /// Panics if the variable (or any variable used in the function) does not exist or if
+    /// it already has a function defined.
+    pub fn update_function(&mut self, variable: &VariableId, function: BooleanExpression) {
         if !self.variable_names.contains_key(variable) {
             panic!("Variable #{} does not exist in this boolean network.", variable);
         }
+        for input in function.support() {
+            if !self.variable_names.contains_key(&input) {
+                panic!("Variable #{} used in the update function does not exist in this boolean network.", input);
+            }
+        }
         if self.update_functions.contains_key(variable) {
             panic!("Cannot redefine update function for {}.", self.variable_names[variable])
         }
@@ -182,18 +323,61 @@ impl BooleanNetworkBuilder {
     }
 
     /// Consume this builder into a full-fledged boolean network.
-    pub fn build_network(mut self) -> BooleanNetwork {
+    pub fn build_network(self) -> BooleanNetwork {
+        if self.variable_count > 32 {
+            panic!("Cannot create network with more than 32 variables.");
+        }
+        let (variable_names, update_functions) = self.into_parts();
+        return BooleanNetwork {
+            variable_names,
+            compiled_functions: update_functions.iter().map(CompiledExpression::compile).collect(),
+            update_functions
+        }
+    }
+
+    /// Consume this builder into variable names and update functions, both ordered by
+    /// variable indices. Panics if some update function is missing.
+    pub(crate) fn into_parts(mut self) -> (Vec<String>, Vec<BooleanExpression>) {
         for (var, name) in self.variable_names.iter() {
             if !self.update_functions.contains_key(var) {
                 panic!("Update function for {} not specified.", name)
             }
         }
-        let mut functions: Vec<(VariableId, Box<dyn Fn(StateId) -> bool + Sync>)> = self.update_functions.drain().collect();
-        functions.sort_by_key(|&(k, _)| k.value);
+        let mut functions: Vec<(VariableId, BooleanExpression)> = self.update_functions.drain().collect();
+        functions.sort_by_key(|(k, _)| k.value);
 
-        return BooleanNetwork {
-            update_functions: functions.into_iter().map(|(_, f)| f).collect()
+        let mut names: Vec<(VariableId, String)> = self.variable_names.drain().collect();
+        names.sort_by_key(|(k, _)| k.value);
+
+        return (
+            names.into_iter().map(|(_, name)| name).collect(),
+            functions.into_iter().map(|(_, f)| f).collect()
+        );
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::demo_model_bistable;
+
+    #[test]
+    fn network_variable_names() {
+        let network = demo_model_bistable();
+        let dna = network.find_variable("DNA").unwrap();
+        assert_eq!("DNA", network.get_variable_name(&dna));
+        assert_eq!(None, network.find_variable("dna"));
+        for v in network.variables() {
+            assert_eq!(Some(v), network.find_variable(network.get_variable_name(&v)));
         }
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn network_format_state() {
+        let network = demo_model_bistable();
+        assert_eq!("P53=1 DNA=1 M2C=1 M2N=0", network.format_state(&StateId { value: 0b0111 }));
+        assert_eq!("P53=0 DNA=1 M2C=0 M2N=1", network.format_state(&StateId { value: 0b1010 }));
+    }
+
+}
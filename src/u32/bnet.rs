@@ -0,0 +1,427 @@
+//! Reading and writing of boolean networks in the `.bnet` format (used by BoolNet, PyBoolNet
+//! or CellCollective).
+//!
+//! Every line of the file defines an update function of one variable as `target, factor`,
+//! where the factor is a boolean formula using `!`, `&`, `|`, parentheses, constants `0`/`1`
+//! and names of other variables. Empty lines and comments starting with `#` are ignored,
+//! as well as the optional `targets, factors` header.
+//!
+//! Since the format does not support other operators, exclusive or, implication and
+//! equivalence are expanded using negation, conjunction and disjunction when writing.
+
+use crate::u32::bn::{BooleanNetwork, BooleanNetworkBuilder, VariableId};
+use crate::u32::expression::{BooleanExpression, var};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Error};
+use std::io::Write;
+
+/// Error encountered while reading a `.bnet` file. Lines and columns are numbered from one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Const(bool),
+    Not, And, Or,
+    Open, Close
+}
+
+/// Token together with its column in the original line.
+type Positioned = (Token, usize);
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_fmt(format_args!("Line {}, column {}: {}", self.line, self.column, self.message))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error<T>(line: usize, column: usize, message: String) -> Result<T, ParseError> {
+    return Err(ParseError { line, column, message });
+}
+
+fn is_name_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_' || c == '.' || c == ':';
+}
+
+/// Names which would be read as constants cannot be used as variable names.
+fn is_valid_name(name: &str) -> bool {
+    return !name.is_empty() && name.chars().all(is_name_char) && !["0", "1", "true", "false"].contains(&name);
+}
+
+/// Read a boolean network from a string in the `.bnet` format.
+pub fn parse_bnet(input: &str) -> Result<BooleanNetwork, ParseError> {
+    return parse_bnet_builder(input, 32).map(|builder| builder.build_network());
+}
+
+/// Read a `.bnet` string into a builder which allows up to [variable_limit] variables.
+pub(crate) fn parse_bnet_builder(input: &str, variable_limit: usize) -> Result<BooleanNetworkBuilder, ParseError> {
+    // (line number, target, target column, factor, factor column)
+    let mut definitions: Vec<(usize, String, usize, String, usize)> = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let line_number = line_index + 1;
+        let content = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line
+        };
+        if content.trim().is_empty() { continue }
+        let comma = match content.find(',') {
+            Some(comma) => comma,
+            None => return error(line_number, 1, format!("Expected `target, factor`, found `{}`.", content.trim()))
+        };
+        let target = content[..comma].trim();
+        let factor = content[(comma + 1)..].trim();
+        if target.eq_ignore_ascii_case("targets") && factor.eq_ignore_ascii_case("factors") { continue }
+        let target_column = column_of(content, content.len() - content.trim_start().len());
+        let factor_column = column_of(content, comma + 1 + (content[(comma + 1)..].len() - content[(comma + 1)..].trim_start().len()));
+        if target.is_empty() || !target.chars().all(is_name_char) {
+            return error(line_number, target_column, format!("Invalid target variable name `{}`.", target));
+        }
+        if factor.is_empty() {
+            return error(line_number, factor_column, format!("Missing update function for {}.", target));
+        }
+        definitions.push((line_number, target.to_string(), target_column, factor.to_string(), factor_column));
+    }
+
+    let mut builder = BooleanNetworkBuilder::with_variable_limit(variable_limit as u32);
+    let mut variables: HashMap<String, VariableId> = HashMap::new();
+    for (line, target, column, _, _) in definitions.iter() {
+        if variables.contains_key(target) {
+            return error(*line, *column, format!("Duplicate update function for {}.", target));
+        }
+        if variables.len() == variable_limit {
+            return error(*line, *column, format!("Cannot create network with more than {} variables.", variable_limit));
+        }
+        variables.insert(target.clone(), builder.make_variable(target));
+    }
+
+    for (line, target, _, factor, column) in definitions.iter() {
+        let tokens = tokenize(factor, *line, *column)?;
+        let mut parser = Parser { tokens: &tokens, position: 0, line: *line, end_column: column + factor.chars().count(), variables: &variables };
+        let expression = parser.parse_or()?;
+        if let Some((token, column)) = tokens.get(parser.position) {
+            return error(*line, *column, format!("Unexpected {:?}.", token));
+        }
+        builder.update_function(&variables[target], expression);
+    }
+
+    return Ok(builder);
+}
+
+/// Write given boolean network to the output writer in the `.bnet` format.
+/// Variables are written in the order of their creation.
+///
+/// Fails with [std::io::ErrorKind::InvalidInput] (before anything is written) if some variable
+/// name could not be read back by [parse_bnet], i.e. it contains other characters than
+/// alphanumerics, `_`, `.` and `:`, or it is a constant (`0`, `1`, `true` or `false`).
+pub fn print_bnet(output: &mut dyn Write, network: &BooleanNetwork) -> Result<(), std::io::Error> {
+    let mut variables: Vec<VariableId> = network.variables().collect();
+    variables.sort();
+    for v in variables.iter() {
+        let name = network.get_variable_name(v);
+        if !is_valid_name(name) {
+            let message = format!("Variable name `{}` cannot be written in the .bnet format.", name);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message));
+        }
+    }
+    output.write_all(b"targets, factors\n")?;
+    for v in variables {
+        let mut factor = String::new();
+        write_factor(&expand(network.get_update_function(&v)), network, &mut factor);
+        output.write_all(format!("{}, {}\n", network.get_variable_name(&v), factor).as_bytes())?;
+    }
+    return Ok(());
+}
+
+/// Convert the given boolean network to a `.bnet` string. Panics if some variable name
+/// cannot be written, see [print_bnet].
+pub fn as_bnet_string(network: &BooleanNetwork) -> String {
+    let mut buffer: Vec<u8> = Vec::new();
+    print_bnet(&mut buffer, network)
+        .expect("Cannot write network to .bnet string.");
+    return String::from_utf8(buffer)
+        .expect("Invalid UTF formatting in .bnet string.");
+}
+
+/// Rewrite the [expression] to only use operators supported by the `.bnet` format.
+fn expand(expression: &BooleanExpression) -> BooleanExpression {
+    return match expression {
+        BooleanExpression::Const(_) | BooleanExpression::Variable(_) => expression.clone(),
+        BooleanExpression::Not(e) => !expand(e),
+        BooleanExpression::And(l, r) => expand(l) & expand(r),
+        BooleanExpression::Or(l, r) => expand(l) | expand(r),
+        BooleanExpression::Xor(l, r) => {
+            let (l, r) = (expand(l), expand(r));
+            (l.clone() & !r.clone()) | (!l & r)
+        },
+        BooleanExpression::Imp(l, r) => !expand(l) | expand(r),
+        BooleanExpression::Iff(l, r) => {
+            let (l, r) = (expand(l), expand(r));
+            (l.clone() & r.clone()) | (!l & !r)
+        },
+    }
+}
+
+/// Write an [expression] which only uses `!`, `&` and `|`. Parentheses are omitted where
+/// the result still parses into the same expression.
+fn write_factor(expression: &BooleanExpression, network: &BooleanNetwork, result: &mut String) {
+    // priority of operators, higher binds stronger
+    fn priority(e: &BooleanExpression) -> u8 {
+        return match e {
+            BooleanExpression::And(_, _) => 2,
+            BooleanExpression::Or(_, _) => 1,
+            _ => 3
+        }
+    }
+    fn write_child(e: &BooleanExpression, network: &BooleanNetwork, result: &mut String, parenthesize: bool) {
+        if parenthesize { result.push('(') }
+        write_factor(e, network, result);
+        if parenthesize { result.push(')') }
+    }
+    match expression {
+        BooleanExpression::Const(value) => result.push(if *value { '1' } else { '0' }),
+        BooleanExpression::Variable(v) => result.push_str(network.get_variable_name(v)),
+        BooleanExpression::Not(e) => {
+            result.push('!');
+            write_child(e, network, result, priority(e) < 3);
+        },
+        BooleanExpression::And(l, r) | BooleanExpression::Or(l, r) => {
+            let p = priority(expression);
+            write_child(l, network, result, priority(l) < p);
+            result.push_str(if p == 2 { " & " } else { " | " });
+            write_child(r, network, result, priority(r) <= p);
+        },
+        _ => panic!("Expression {:?} must be expanded first.", expression)
+    }
+}
+
+/// Convert a byte offset into a one-based column (counted in characters).
+fn column_of(line: &str, offset: usize) -> usize {
+    return line[..offset].chars().count() + 1;
+}
+
+fn tokenize(factor: &str, line: usize, start_column: usize) -> Result<Vec<Positioned>, ParseError> {
+    let mut result: Vec<Positioned> = Vec::new();
+    let chars: Vec<char> = factor.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = start_column + i;
+        let c = chars[i];
+        i += 1;
+        match c {
+            ' ' | '\t' => continue,
+            '!' => result.push((Token::Not, column)),
+            '&' => result.push((Token::And, column)),
+            '|' => result.push((Token::Or, column)),
+            '(' => result.push((Token::Open, column)),
+            ')' => result.push((Token::Close, column)),
+            _ if is_name_char(c) => {
+                let mut name = c.to_string();
+                while i < chars.len() && is_name_char(chars[i]) {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+                let token = match name.as_str() {
+                    "0" | "false" => Token::Const(false),
+                    "1" | "true" => Token::Const(true),
+                    _ => Token::Name(name)
+                };
+                result.push((token, column));
+            }
+            _ => return error(line, column, format!("Unexpected character `{}`.", c))
+        }
+    }
+    return Ok(result);
+}
+
+/// Simple recursive descent parser. Negation has the highest priority, followed by
+/// conjunction and disjunction. Binary operators are left-associative.
+struct Parser<'a> {
+    tokens: &'a [Positioned],
+    position: usize,
+    line: usize,
+    end_column: usize,
+    variables: &'a HashMap<String, VariableId>
+}
+
+impl Parser<'_> {
+
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.position).map(|(t, _)| t);
+    }
+
+    fn parse_or(&mut self) -> Result<BooleanExpression, ParseError> {
+        let mut result = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            result = result | self.parse_and()?;
+        }
+        return Ok(result);
+    }
+
+    fn parse_and(&mut self) -> Result<BooleanExpression, ParseError> {
+        let mut result = self.parse_atom()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            result = result & self.parse_atom()?;
+        }
+        return Ok(result);
+    }
+
+    fn parse_atom(&mut self) -> Result<BooleanExpression, ParseError> {
+        let (token, column) = match self.tokens.get(self.position) {
+            Some((token, column)) => (token.clone(), *column),
+            None => return error(self.line, self.end_column, "Unexpected end of formula.".to_string())
+        };
+        self.position += 1;
+        return match token {
+            Token::Not => Ok(!self.parse_atom()?),
+            Token::Const(value) => Ok(BooleanExpression::Const(value)),
+            Token::Name(name) => match self.variables.get(&name) {
+                Some(variable) => Ok(var(*variable)),
+                None => error(self.line, column, format!("Unknown variable {} (it has no update function).", name))
+            },
+            Token::Open => {
+                let inner = self.parse_or()?;
+                match self.tokens.get(self.position) {
+                    Some((Token::Close, _)) => { self.position += 1; Ok(inner) },
+                    Some((token, column)) => error(self.line, *column, format!("Expected `)`, found {:?}.", token)),
+                    None => error(self.line, self.end_column, "Expected `)`, found end of formula.".to_string())
+                }
+            },
+            _ => error(self.line, column, format!("Unexpected {:?}.", token))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::bn::AsyncNetwork;
+    use crate::u32::models::*;
+
+    const BISTABLE: &str = "targets, factors
+# A small demo model
+P53, !M2N
+DNA, !P53 | DNA
+M2C, P53
+M2N, (M2C & (!DNA | !P53)) | (!M2C & !P53)   # trailing comment
+";
+
+    fn parse_error(input: &str) -> (usize, usize) {
+        let e = parse_bnet(input).err().expect("Expected parse error.");
+        return (e.line, e.column);
+    }
+
+    #[test]
+    fn bnet_parse_demo_model() {
+        let parsed = parse_bnet(BISTABLE).unwrap();
+        let expected = demo_model_bistable();
+        assert_eq!(expected.var_count(), parsed.var_count());
+        for s in expected.states() {
+            for v in expected.variables() {
+                assert_eq!(expected.successor(&s, &v), parsed.successor(&s, &v));
+            }
+        }
+    }
+
+    #[test]
+    fn bnet_parse_constants_and_precedence() {
+        let network = parse_bnet("a, 1\nb, !a | a & 0\n").unwrap();
+        let a = network.find_variable("a").unwrap();
+        let b = network.find_variable("b").unwrap();
+        assert_eq!(&BooleanExpression::Const(true), network.get_update_function(&a));
+        assert_eq!(&(!var(a) | (var(a) & BooleanExpression::Const(false))), network.get_update_function(&b));
+    }
+
+    #[test]
+    fn bnet_parse_errors() {
+        assert_eq!((2, 8), parse_error("a, a\nb, a & c"));
+        assert_eq!((3, 1), parse_error("a, a\nb, a\nc"));
+        assert_eq!((1, 4), parse_error("a, \nb, b"));
+        assert_eq!((2, 6), parse_error("a, b\nb, a $ b"));
+        assert_eq!((1, 10), parse_error("a, (a & a"));
+        assert_eq!((1, 6), parse_error("a, a a"));
+        assert_eq!((2, 1), parse_error("a, a\na, !a"));
+        assert_eq!((1, 7), parse_error("a, a & "));
+    }
+
+    #[test]
+    fn bnet_parse_error_message() {
+        let e = parse_bnet("a, a & x").err().unwrap();
+        assert_eq!("Line 1, column 8: Unknown variable x (it has no update function).", e.to_string());
+    }
+
+    #[test]
+    fn bnet_write_demo_model() {
+        let expected = "targets, factors
+P53, !M2N
+DNA, !P53 | DNA
+M2C, P53
+M2N, M2C & (!DNA | !P53) | !M2C & !P53
+";
+        assert_eq!(expected, as_bnet_string(&demo_model_bistable()));
+    }
+
+    #[test]
+    fn bnet_write_expanded_operators() {
+        let mut builder = BooleanNetworkBuilder::new();
+        let a = builder.make_variable("a");
+        let b = builder.make_variable("b");
+        builder.update_function(&a, (var(a) ^ var(b)).iff(BooleanExpression::Const(false)));
+        builder.update_function(&b, var(a).imp(!(var(b) | var(a))));
+        let network = builder.build_network();
+        let parsed = parse_bnet(&as_bnet_string(&network)).unwrap();
+        for s in network.states() {
+            for v in network.variables() {
+                assert_eq!(network.successor(&s, &v), parsed.successor(&s, &v));
+            }
+        }
+    }
+
+    #[test]
+    fn bnet_write_invalid_names() {
+        for name in &["x-1", "a b", "", "true", "0"] {
+            let mut builder = BooleanNetworkBuilder::new();
+            let a = builder.make_variable("a");
+            let x = builder.make_variable(name);
+            builder.update_function(&a, var(x));
+            builder.update_function(&x, var(a));
+            let mut buffer: Vec<u8> = Vec::new();
+            let error = print_bnet(&mut buffer, &builder.build_network()).err().unwrap();
+            assert_eq!(std::io::ErrorKind::InvalidInput, error.kind());
+            assert!(buffer.is_empty());
+        }
+    }
+
+    #[test]
+    fn bnet_round_trip() {
+        for network in &[hspc_model(), t2dm_model(), erb_b2_model(), demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let written = as_bnet_string(network);
+            let parsed = parse_bnet(&written).unwrap();
+            for v in network.variables() {
+                assert_eq!(network.get_variable_name(&v), parsed.get_variable_name(&v));
+                assert_eq!(network.get_update_function(&v), parsed.get_update_function(&v));
+            }
+            assert_eq!(written, as_bnet_string(&parsed));
+        }
+    }
+
+    #[test]
+    fn network_to_string() {
+        let expected = "P53 := !M2N
+DNA := !P53 | DNA
+M2C := P53
+M2N := M2C & (!DNA | !P53) | !M2C & !P53
+";
+        assert_eq!(expected, demo_model_bistable().to_string());
+    }
+
+}
@@ -0,0 +1,200 @@
+use crate::u32::bn::BooleanNetwork;
+use crate::u32::decomposition::{SccDecomposition, ComponentId, ComponentIterator};
+use crate::bitset::BitSet;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Condensation (quotient graph) of the asynchronous state space. There is one node for
+/// every component of a [SccDecomposition] and an edge between two components whenever
+/// some transition leads from a state of the first one into a state of the second one.
+///
+/// Every edge remembers its multiplicity, i.e. the number of state transitions it represents.
+/// Since components are maximal, the condensation is always acyclic.
+pub struct Condensation {
+    component_sizes: Vec<u64>,
+    // successors of component c are stored in edges[edge_start[c]..edge_start[c+1]],
+    // sorted by target component
+    edge_start: Vec<usize>,
+    edges: Vec<(ComponentId, u64)>
+}
+
+impl Condensation {
+
+    /// Build the condensation of the [network] state space partitioned by [decomposition].
+    pub fn new(network: &BooleanNetwork, decomposition: &SccDecomposition) -> Condensation {
+        let mut multiplicity: HashMap<(ComponentId, ComponentId), u64> = HashMap::new();
+        for s in network.states() {
+            let source = decomposition.component_of(&s);
+            for var in network.variables() {
+                if let Some(t) = network.successor(&s, &var) {
+                    let target = decomposition.component_of(&t);
+                    if source != target {
+                        *multiplicity.entry((source, target)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut sorted: Vec<((ComponentId, ComponentId), u64)> = multiplicity.into_iter().collect();
+        sorted.sort();
+
+        let component_count = decomposition.component_count();
+        let mut edge_start: Vec<usize> = vec![0; component_count + 1];
+        for ((source, _), _) in sorted.iter() {
+            edge_start[source.value as usize + 1] += 1;
+        }
+        for c in 0..component_count {
+            edge_start[c + 1] += edge_start[c];
+        }
+
+        return Condensation {
+            component_sizes: decomposition.components().map(|c| decomposition.component_size(&c)).collect(),
+            edge_start,
+            edges: sorted.into_iter().map(|((_, target), count)| (target, count)).collect()
+        }
+    }
+
+    /// Number of nodes (components) of the condensation.
+    pub fn component_count(&self) -> usize {
+        return self.component_sizes.len();
+    }
+
+    /// Number of edges of the condensation.
+    pub fn edge_count(&self) -> usize {
+        return self.edges.len();
+    }
+
+    /// Iterate over all components of the condensation.
+    pub fn components(&self) -> ComponentIterator {
+        return ComponentIterator::new(self.component_count() as u32);
+    }
+
+    /// Number of states in the given [component].
+    pub fn component_size(&self, component: &ComponentId) -> u64 {
+        return self.component_sizes[component.value as usize];
+    }
+
+    /// Successors of the given [component] together with edge multiplicities, sorted by IDs.
+    pub fn successors(&self, component: &ComponentId) -> &[(ComponentId, u64)] {
+        let c = component.value as usize;
+        return &self.edges[self.edge_start[c]..self.edge_start[c + 1]];
+    }
+
+    /// Iterate over all edges of the condensation as (source, target, multiplicity) triples.
+    pub fn edges(&self) -> impl Iterator<Item = (ComponentId, ComponentId, u64)> + '_ {
+        return self.components().flat_map(move |source| {
+            self.successors(&source).iter().map(move |(target, count)| (source, *target, *count))
+        });
+    }
+
+    /// A component is terminal if it has no successors (i.e. it is an attractor).
+    pub fn is_terminal(&self, component: &ComponentId) -> bool {
+        return self.successors(component).is_empty();
+    }
+
+    /// Compute all components reachable from the given [component] (including itself),
+    /// sorted by IDs.
+    pub fn reachable_from(&self, component: &ComponentId) -> Vec<ComponentId> {
+        let mut visited = BitSet::new_empty(self.component_count());
+        let mut stack: Vec<ComponentId> = vec![*component];
+        visited.set(component.value as usize);
+        while let Some(c) = stack.pop() {
+            for (t, _) in self.successors(&c) {
+                if !visited.is_set(t.value as usize) {
+                    visited.set(t.value as usize);
+                    stack.push(*t);
+                }
+            }
+        }
+        return self.components().filter(|c| visited.is_set(c.value as usize)).collect();
+    }
+
+    /// Compute the terminal components reachable from the given [component], i.e. the
+    /// attractors to which the [component] can commit.
+    pub fn reachable_terminals(&self, component: &ComponentId) -> Vec<ComponentId> {
+        return self.reachable_from(component).into_iter()
+            .filter(|c| self.is_terminal(c))
+            .collect();
+    }
+
+    /// Convert the condensation to a .dot file string.
+    pub fn as_dot_string(&self) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        print_condensation_as_dot(&mut buffer, self)
+            .expect("Cannot write condensation to .dot string.");
+        return String::from_utf8(buffer)
+            .expect("Invalid UTF formatting in .dot string.");
+    }
+
+}
+
+/// Write given condensation to the output writer as a .dot graph.
+/// Every node is labelled by its component ID and size, every edge by its multiplicity.
+/// Terminal components are filled.
+pub fn print_condensation_as_dot(
+    output: &mut dyn Write,
+    condensation: &Condensation
+) -> Result<(), std::io::Error> {
+    output.write_all(b"digraph G {\n")?;
+    for c in condensation.components() {
+        let style = if condensation.is_terminal(&c) { ", style=filled" } else { "" };
+        output.write_all(format!("{}[label=\"{} ({})\"{}];\n", c.value, c.value, condensation.component_size(&c), style).as_bytes())?;
+    }
+    for (source, target, count) in condensation.edges() {
+        output.write_all(format!("{} -> {} [label=\"{}\"];\n", source.value, target.value, count).as_bytes())?;
+    }
+    output.write_all(b"}\n")?;
+    return Result::Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::*;
+    use crate::u32::sequential::{scc, attractors};
+
+    fn load_expected_results(test_name: &str) -> String {
+        return std::fs::read_to_string(format!("test_results/u32/{}", test_name)).expect("Cannot open result file.")
+    }
+
+    #[test]
+    fn condensation_is_acyclic() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let decomposition = scc(network);
+            let condensation = Condensation::new(network, &decomposition);
+            assert_eq!(decomposition.component_count(), condensation.component_count());
+            for (source, target, count) in condensation.edges() {
+                assert!(count > 0);
+                assert!(!condensation.reachable_from(&target).contains(&source));
+            }
+            let terminal_count = condensation.components().filter(|c| condensation.is_terminal(c)).count();
+            assert_eq!(attractors(network).len(), terminal_count);
+        }
+    }
+
+    #[test]
+    fn condensation_multiplicities() {
+        let network = demo_model_oscillation();
+        let decomposition = scc(&network);
+        let condensation = Condensation::new(&network, &decomposition);
+        let mut crossing: u64 = 0;
+        for s in network.states() {
+            for v in network.variables() {
+                if let Some(t) = network.successor(&s, &v) {
+                    if decomposition.component_of(&s) != decomposition.component_of(&t) {
+                        crossing += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(crossing, condensation.edges().map(|(_, _, count)| count).sum());
+    }
+
+    #[test]
+    fn condensation_to_dot() {
+        let network = demo_model_oscillation();
+        let condensation = Condensation::new(&network, &scc(&network));
+        assert_eq!(load_expected_results("condensation_to_dot.dot"), condensation.as_dot_string());
+    }
+
+}
@@ -0,0 +1,277 @@
+use crate::u32::bn::{StateId, BooleanNetwork};
+use std::fmt::{Display, Formatter, Error};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A component ID is a dense index (0..component_count) of one SCC in a [SccDecomposition].
+///
+/// Components are numbered in the order of their smallest state, hence two decompositions
+/// describing the same partition of the state space always assign the same IDs.
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone, Ord, PartialOrd)]
+pub struct ComponentId { pub value: u32 }
+
+/// Component iterator goes through all components of a [SccDecomposition] in the order
+/// of their IDs.
+pub struct ComponentIterator {
+    next: u32, count: u32
+}
+
+/// SCC decomposition of the state space of a [BooleanNetwork].
+///
+/// Every state is assigned exactly one component. Apart from the state-to-component mapping,
+/// the decomposition keeps the states sorted by components, so the states of one component
+/// can be listed without scanning the whole state space. This costs 8 bytes per state.
+///
+/// Since component IDs are canonical, two decompositions are equal exactly when they
+/// describe the same partition of the state space.
+#[derive(PartialEq, Eq)]
+pub struct SccDecomposition {
+    // component_of[s] is the ID of the component of state s
+    component_of: Vec<u32>,
+    table: ComponentTable<StateId>
+}
+
+/// SCC decomposition of the part of the state space which is reachable from some initial
+/// states (see [crate::u32::sequential::scc_reachable]). Unlike [SccDecomposition], the memory
+/// requirements only depend on the number of covered states. The type of states is generic,
+/// so the same decomposition is also used for networks with wider states (see [crate::u64]).
+///
+/// Components are numbered in the order of their smallest state, as in [SccDecomposition].
+#[derive(PartialEq, Eq)]
+pub struct ReachableSccDecomposition<S = StateId> where S: Eq + Hash {
+    component_of: HashMap<S, u32>,
+    table: ComponentTable<S>
+}
+
+/// States of a decomposition sorted by components, shared by [SccDecomposition] and
+/// [ReachableSccDecomposition].
+#[derive(PartialEq, Eq)]
+struct ComponentTable<S> {
+    // states of component c are stored in states[component_start[c]..component_start[c+1]]
+    component_start: Vec<usize>,
+    states: Vec<S>
+}
+
+/// Marks states which have not been assigned a component yet during construction.
+const UNASSIGNED: u32 = std::u32::MAX;
+
+impl Display for ComponentId {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_fmt(format_args!("Component({})", self.value))
+    }
+}
+
+impl ComponentIterator {
+
+    /// Make an iterator over components 0..[count].
+    pub(crate) fn new(count: u32) -> ComponentIterator {
+        return ComponentIterator { next: 0, count }
+    }
+
+}
+
+impl Iterator for ComponentIterator {
+    type Item = ComponentId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return if self.next == self.count { None } else {
+            self.next += 1;
+            Some(ComponentId { value: self.next - 1 })
+        }
+    }
+
+}
+
+impl SccDecomposition {
+
+    /// Build the decomposition from a partition of the state space given by [find_root],
+    /// which maps every state to a representative of its component (typically a root
+    /// of some union-find structure).
+    pub(crate) fn from_partition<F>(network: &BooleanNetwork, mut find_root: F) -> SccDecomposition
+        where F: FnMut(&StateId) -> usize
+    {
+        let mut component_of: Vec<u32> = vec![UNASSIGNED; network.state_count() as usize];
+        let mut component_count: u32 = 0;
+        // States are visited in increasing order, so the first state that reaches a root
+        // is the smallest state of that component. The ID of the component is temporarily
+        // stored at the position of the root - since the root belongs to the same component,
+        // the value is valid also once the root state itself is visited.
+        for s in network.states() {
+            let root = find_root(&s);
+            if component_of[root] == UNASSIGNED {
+                component_of[root] = component_count;
+                component_count += 1;
+            }
+            component_of[s.value as usize] = component_of[root];
+        }
+
+        let table = ComponentTable::new(|| network.states(), component_count as usize, |s| component_of[s.value as usize]);
+        return SccDecomposition { component_of, table }
+    }
+
+    /// Number of components in this decomposition.
+    pub fn component_count(&self) -> usize {
+        return self.table.component_count();
+    }
+
+    /// Number of components with more than one state.
+    pub fn non_trivial_count(&self) -> usize {
+        return self.table.non_trivial_components().count();
+    }
+
+    /// Iterate over all components of this decomposition.
+    pub fn components(&self) -> ComponentIterator {
+        return self.table.components();
+    }
+
+    /// Iterate over components which contain more than one state.
+    pub fn non_trivial_components(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        return self.table.non_trivial_components();
+    }
+
+    /// Find the component of the given [state].
+    pub fn component_of(&self, state: &StateId) -> ComponentId {
+        return ComponentId { value: self.component_of[state.value as usize] }
+    }
+
+    /// Number of states in the given [component].
+    pub fn component_size(&self, component: &ComponentId) -> u64 {
+        return self.table.component_size(component);
+    }
+
+    /// A component is trivial if it consists of a single state.
+    pub fn is_trivial(&self, component: &ComponentId) -> bool {
+        return self.table.component_size(component) == 1;
+    }
+
+    /// Iterate over states of the given [component] in increasing order.
+    pub fn states_of(&self, component: &ComponentId) -> impl Iterator<Item = StateId> + '_ {
+        return self.table.states_of(component);
+    }
+
+}
+
+impl <S> ReachableSccDecomposition<S> where S: Copy + Eq + Ord + Hash {
+
+    /// Build the decomposition of the given [states] partitioned by [find_root], which maps
+    /// every state to a representative of its component.
+    pub(crate) fn from_partition<F>(mut states: Vec<S>, mut find_root: F) -> ReachableSccDecomposition<S>
+        where F: FnMut(S) -> S
+    {
+        states.sort();
+        let mut root_component: HashMap<S, u32> = HashMap::new();
+        let mut component_of: HashMap<S, u32> = HashMap::with_capacity(states.len());
+        // States are visited in increasing order, so components are numbered by their smallest state.
+        for s in states.iter() {
+            let next_id = root_component.len() as u32;
+            let component = *root_component.entry(find_root(*s)).or_insert(next_id);
+            component_of.insert(*s, component);
+        }
+
+        let table = ComponentTable::new(|| states.iter().cloned(), root_component.len(), |s| component_of[s]);
+        return ReachableSccDecomposition { component_of, table }
+    }
+
+    /// Number of states covered by this decomposition.
+    pub fn state_count(&self) -> usize {
+        return self.table.states.len();
+    }
+
+    /// Check whether the [state] is covered by this decomposition.
+    pub fn contains(&self, state: &S) -> bool {
+        return self.component_of.contains_key(state);
+    }
+
+    /// Number of components in this decomposition.
+    pub fn component_count(&self) -> usize {
+        return self.table.component_count();
+    }
+
+    /// Number of components with more than one state.
+    pub fn non_trivial_count(&self) -> usize {
+        return self.table.non_trivial_components().count();
+    }
+
+    /// Iterate over all components of this decomposition.
+    pub fn components(&self) -> ComponentIterator {
+        return self.table.components();
+    }
+
+    /// Iterate over components which contain more than one state.
+    pub fn non_trivial_components(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        return self.table.non_trivial_components();
+    }
+
+    /// Find the component of the given [state], or [None] if the state is not covered
+    /// by this decomposition.
+    pub fn component_of(&self, state: &S) -> Option<ComponentId> {
+        return self.component_of.get(state).map(|c| ComponentId { value: *c });
+    }
+
+    /// Number of states in the given [component].
+    pub fn component_size(&self, component: &ComponentId) -> u64 {
+        return self.table.component_size(component);
+    }
+
+    /// A component is trivial if it consists of a single state.
+    pub fn is_trivial(&self, component: &ComponentId) -> bool {
+        return self.table.component_size(component) == 1;
+    }
+
+    /// Iterate over states of the given [component] in increasing order.
+    pub fn states_of(&self, component: &ComponentId) -> impl Iterator<Item = S> + '_ {
+        return self.table.states_of(component);
+    }
+
+}
+
+impl <S> ComponentTable<S> where S: Copy {
+
+    /// Sort [states] into [component_count] components given by [component_of]. The sort
+    /// is stable, so states which are given in increasing order stay sorted within components.
+    fn new<I, G, F>(states: G, component_count: usize, component_of: F) -> ComponentTable<S>
+        where I: Iterator<Item = S>, G: Fn() -> I, F: Fn(&S) -> u32
+    {
+        // Counting sort of states by components.
+        let mut component_start: Vec<usize> = vec![0; component_count + 1];
+        for s in states() {
+            component_start[component_of(&s) as usize + 1] += 1;
+        }
+        for c in 0..component_count {
+            component_start[c + 1] += component_start[c];
+        }
+        let mut next_free: Vec<usize> = component_start[0..component_count].to_vec();
+        // The initial values are only placeholders, every position is overwritten below.
+        let mut sorted: Vec<S> = states().collect();
+        for s in states() {
+            let c = component_of(&s) as usize;
+            sorted[next_free[c]] = s;
+            next_free[c] += 1;
+        }
+
+        return ComponentTable { component_start, states: sorted }
+    }
+
+    fn component_count(&self) -> usize {
+        return self.component_start.len() - 1;
+    }
+
+    fn components(&self) -> ComponentIterator {
+        return ComponentIterator::new(self.component_count() as u32);
+    }
+
+    fn non_trivial_components(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        return self.components().filter(move |c| self.component_size(c) > 1);
+    }
+
+    fn component_size(&self, component: &ComponentId) -> u64 {
+        let c = component.value as usize;
+        return (self.component_start[c + 1] - self.component_start[c]) as u64;
+    }
+
+    fn states_of(&self, component: &ComponentId) -> impl Iterator<Item = S> + '_ {
+        let c = component.value as usize;
+        return self.states[self.component_start[c]..self.component_start[c + 1]].iter().cloned();
+    }
+
+}
@@ -0,0 +1,307 @@
+use crate::u32::bn::{StateId, VariableId, BooleanNetwork};
+use crate::bitset::BitSet;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+/// Boolean expression over the variables of a [BooleanNetwork], used to describe
+/// update functions.
+///
+/// For convenience, expressions can be combined using standard operators: `!` (negation),
+/// `&` (conjunction), `|` (disjunction) and `^` (exclusive or). Implication and equivalence
+/// are available as [BooleanExpression::imp] and [BooleanExpression::iff].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BooleanExpression {
+    Const(bool),
+    Variable(VariableId),
+    Not(Box<BooleanExpression>),
+    And(Box<BooleanExpression>, Box<BooleanExpression>),
+    Or(Box<BooleanExpression>, Box<BooleanExpression>),
+    Xor(Box<BooleanExpression>, Box<BooleanExpression>),
+    Imp(Box<BooleanExpression>, Box<BooleanExpression>),
+    Iff(Box<BooleanExpression>, Box<BooleanExpression>)
+}
+
+/// Update functions which depend on at most this many variables are compiled into truth tables.
+const TABLE_INPUT_LIMIT: usize = 16;
+
+/// Boolean expression in a form suitable for fast evaluation. If the expression depends on
+/// few variables, it is stored as an explicit truth table indexed by the values of these
+/// variables. Otherwise, the original expression is evaluated recursively.
+///
+/// The compiled form does not depend on the representation of states, since variable
+/// values are always obtained through a function.
+pub(crate) enum CompiledExpression {
+    Table { inputs: Vec<VariableId>, table: BitSet },
+    Expression
+}
+
+/// Shorthand for creating a [BooleanExpression::Variable] expression.
+pub fn var(variable: VariableId) -> BooleanExpression {
+    return BooleanExpression::Variable(variable);
+}
+
+impl BooleanExpression {
+
+    /// Create an expression `self => other`.
+    pub fn imp(self, other: BooleanExpression) -> BooleanExpression {
+        return BooleanExpression::Imp(Box::new(self), Box::new(other));
+    }
+
+    /// Create an expression `self <=> other`.
+    pub fn iff(self, other: BooleanExpression) -> BooleanExpression {
+        return BooleanExpression::Iff(Box::new(self), Box::new(other));
+    }
+
+    /// Evaluate this expression in the given [state].
+    pub fn evaluate(&self, state: StateId) -> bool {
+        return self.evaluate_with(&|v| state | v);
+    }
+
+    /// Evaluate this expression using the given valuation of variables.
+    pub fn evaluate_with<F>(&self, value: &F) -> bool where F: Fn(VariableId) -> bool {
+        return match self {
+            BooleanExpression::Const(constant) => *constant,
+            BooleanExpression::Variable(v) => value(*v),
+            BooleanExpression::Not(e) => !e.evaluate_with(value),
+            BooleanExpression::And(l, r) => l.evaluate_with(value) && r.evaluate_with(value),
+            BooleanExpression::Or(l, r) => l.evaluate_with(value) || r.evaluate_with(value),
+            BooleanExpression::Xor(l, r) => l.evaluate_with(value) != r.evaluate_with(value),
+            BooleanExpression::Imp(l, r) => !l.evaluate_with(value) || r.evaluate_with(value),
+            BooleanExpression::Iff(l, r) => l.evaluate_with(value) == r.evaluate_with(value),
+        }
+    }
+
+    /// Variables which appear in this expression, sorted and without duplicates.
+    pub fn support(&self) -> Vec<VariableId> {
+        let mut result: Vec<VariableId> = Vec::new();
+        self.collect_support(&mut result);
+        result.sort();
+        result.dedup();
+        return result;
+    }
+
+    /// Convert this expression to a human-readable string, using variable names from the
+    /// given [network]. Parentheses are only added where necessary, assuming the usual operator
+    /// priority (from highest): `!`, `&`, `^`, `|`, `=>`, `<=>`.
+    pub fn format(&self, network: &BooleanNetwork) -> String {
+        let mut result = String::new();
+        self.format_into(network, &mut result);
+        return result;
+    }
+
+    /// Priority of the top-level operator of this expression (higher binds stronger).
+    fn priority(&self) -> u8 {
+        return match self {
+            BooleanExpression::Const(_) | BooleanExpression::Variable(_) | BooleanExpression::Not(_) => 6,
+            BooleanExpression::And(_, _) => 5,
+            BooleanExpression::Xor(_, _) => 4,
+            BooleanExpression::Or(_, _) => 3,
+            BooleanExpression::Imp(_, _) => 2,
+            BooleanExpression::Iff(_, _) => 1,
+        }
+    }
+
+    fn format_into(&self, network: &BooleanNetwork, result: &mut String) {
+        let (l, r, operator) = match self {
+            BooleanExpression::Const(value) => {
+                result.push_str(if *value { "true" } else { "false" });
+                return;
+            },
+            BooleanExpression::Variable(v) => {
+                result.push_str(network.get_variable_name(v));
+                return;
+            },
+            BooleanExpression::Not(e) => {
+                result.push('!');
+                e.format_child(network, result, e.priority() < self.priority());
+                return;
+            },
+            BooleanExpression::And(l, r) => (l, r, " & "),
+            BooleanExpression::Or(l, r) => (l, r, " | "),
+            BooleanExpression::Xor(l, r) => (l, r, " ^ "),
+            BooleanExpression::Imp(l, r) => (l, r, " => "),
+            BooleanExpression::Iff(l, r) => (l, r, " <=> "),
+        };
+        // Binary operators are printed as left-associative, hence the right child
+        // needs parentheses also when it has the same priority.
+        l.format_child(network, result, l.priority() < self.priority());
+        result.push_str(operator);
+        r.format_child(network, result, r.priority() <= self.priority());
+    }
+
+    fn format_child(&self, network: &BooleanNetwork, result: &mut String, parenthesize: bool) {
+        if parenthesize { result.push('(') }
+        self.format_into(network, result);
+        if parenthesize { result.push(')') }
+    }
+
+    fn collect_support(&self, result: &mut Vec<VariableId>) {
+        match self {
+            BooleanExpression::Const(_) => (),
+            BooleanExpression::Variable(v) => result.push(*v),
+            BooleanExpression::Not(e) => e.collect_support(result),
+            BooleanExpression::And(l, r) | BooleanExpression::Or(l, r) |
+            BooleanExpression::Xor(l, r) | BooleanExpression::Imp(l, r) |
+            BooleanExpression::Iff(l, r) => {
+                l.collect_support(result);
+                r.collect_support(result);
+            }
+        }
+    }
+
+}
+
+impl CompiledExpression {
+
+    pub(crate) fn compile(expression: &BooleanExpression) -> CompiledExpression {
+        let inputs = expression.support();
+        return if inputs.len() > TABLE_INPUT_LIMIT { CompiledExpression::Expression } else {
+            let table_size = 1_usize << inputs.len();
+            let mut table = BitSet::new_empty(table_size);
+            for index in 0..table_size {
+                // the i-th input has the value of the i-th bit of the table index
+                let value = |v: VariableId| {
+                    let i = inputs.binary_search(&v).unwrap();
+                    (index >> i) & 1 == 1
+                };
+                if expression.evaluate_with(&value) { table.set(index) }
+            }
+            CompiledExpression::Table { inputs, table }
+        }
+    }
+
+    /// Evaluate the compiled form of the given [expression] using the given valuation
+    /// of variables.
+    pub(crate) fn evaluate<F>(&self, expression: &BooleanExpression, value: F) -> bool
+        where F: Fn(VariableId) -> bool
+    {
+        return match self {
+            CompiledExpression::Table { inputs, table } => {
+                let mut index: usize = 0;
+                for (i, input) in inputs.iter().enumerate() {
+                    if value(*input) { index |= 1 << i }
+                }
+                table.is_set(index)
+            },
+            CompiledExpression::Expression => expression.evaluate_with(&value)
+        }
+    }
+
+}
+
+impl From<VariableId> for BooleanExpression {
+    fn from(variable: VariableId) -> Self {
+        return BooleanExpression::Variable(variable);
+    }
+}
+
+impl From<bool> for BooleanExpression {
+    fn from(value: bool) -> Self {
+        return BooleanExpression::Const(value);
+    }
+}
+
+impl Not for BooleanExpression {
+    type Output = BooleanExpression;
+
+    fn not(self) -> Self::Output {
+        return BooleanExpression::Not(Box::new(self));
+    }
+
+}
+
+impl BitAnd for BooleanExpression {
+    type Output = BooleanExpression;
+
+    fn bitand(self, rhs: BooleanExpression) -> Self::Output {
+        return BooleanExpression::And(Box::new(self), Box::new(rhs));
+    }
+
+}
+
+impl BitOr for BooleanExpression {
+    type Output = BooleanExpression;
+
+    fn bitor(self, rhs: BooleanExpression) -> Self::Output {
+        return BooleanExpression::Or(Box::new(self), Box::new(rhs));
+    }
+
+}
+
+impl BitXor for BooleanExpression {
+    type Output = BooleanExpression;
+
+    fn bitxor(self, rhs: BooleanExpression) -> Self::Output {
+        return BooleanExpression::Xor(Box::new(self), Box::new(rhs));
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::bn::BooleanNetworkBuilder;
+
+    #[test]
+    fn expression_evaluate() {
+        let mut builder = BooleanNetworkBuilder::new();
+        let a = builder.make_variable("a");
+        let b = builder.make_variable("b");
+        let state = StateId { value: 0b01 };    // a = 1, b = 0
+        assert!(var(a).evaluate(state));
+        assert!(!var(b).evaluate(state));
+        assert!((var(a) | var(b)).evaluate(state));
+        assert!(!(var(a) & var(b)).evaluate(state));
+        assert!((var(a) ^ var(b)).evaluate(state));
+        assert!(!var(a).imp(var(b)).evaluate(state));
+        assert!(var(b).imp(var(a)).evaluate(state));
+        assert!(!var(a).iff(var(b)).evaluate(state));
+        assert!((!var(b)).evaluate(state));
+        assert!(BooleanExpression::from(true).evaluate(state));
+    }
+
+    #[test]
+    fn expression_support() {
+        let mut builder = BooleanNetworkBuilder::new();
+        let a = builder.make_variable("a");
+        let b = builder.make_variable("b");
+        let c = builder.make_variable("c");
+        let e = (var(c) & !var(a)) | var(c).iff(BooleanExpression::Const(false));
+        assert_eq!(vec![a, c], e.support());
+        assert_eq!(vec![b], var(b).support());
+    }
+
+    #[test]
+    fn expression_format() {
+        let mut builder = BooleanNetworkBuilder::new();
+        let a = builder.make_variable("a");
+        let b = builder.make_variable("b");
+        builder.update_function(&a, var(a));
+        builder.update_function(&b, var(b));
+        let network = builder.build_network();
+        let e = (var(a) ^ var(b) | var(a)).imp(!(var(a) & var(b))).iff(BooleanExpression::Const(true));
+        assert_eq!("a ^ b | a => !(a & b) <=> true", e.format(&network));
+        let e = var(a) & (var(b) & var(a)) ^ !!var(b);
+        assert_eq!("a & (b & a) ^ !!b", e.format(&network));
+    }
+
+    #[test]
+    fn expression_compiled_evaluation() {
+        // the parity function has 17 inputs (above the truth table limit), the other one only 3
+        let mut builder = BooleanNetworkBuilder::new();
+        let vars: Vec<VariableId> = (0..17).map(|i| builder.make_variable(&format!("v{}", i))).collect();
+        let parity = vars.iter().fold(BooleanExpression::Const(false), |e, v| e ^ var(*v));
+        let small = (var(vars[0]) & var(vars[5])) | !var(vars[16]);
+        builder.update_function(&vars[0], parity.clone());
+        builder.update_function(&vars[1], small.clone());
+        for v in &vars[2..] {
+            builder.update_function(v, var(*v));
+        }
+        let network = builder.build_network();
+        for value in (0..(1 << 17)).step_by(97) {
+            let state = StateId { value };
+            assert_eq!(parity.evaluate(state), network.successor(&state, &vars[0]).is_some() != (state | vars[0]));
+            assert_eq!(small.evaluate(state), network.successor(&state, &vars[1]).is_some() != (state | vars[1]));
+        }
+    }
+
+}
@@ -4,6 +4,13 @@
 //! full 64-bit version (every pointer is only 4 bytes instead of 8).
 
 pub mod bn;
+pub mod bnet;
+pub mod condensation;
+pub mod decomposition;
+pub mod expression;
 pub mod models;
 pub mod sequential;
+pub mod symbolic;
+pub mod symbolic_scc;
+pub mod symbolic_attractors;
 pub mod parallel;
\ No newline at end of file
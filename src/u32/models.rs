@@ -1,4 +1,5 @@
 use crate::u32::bn::{BooleanNetwork, BooleanNetworkBuilder};
+use crate::u32::expression::{BooleanExpression, var};
 
 /*
     Regular expressions for simplified model building
@@ -6,16 +7,13 @@ use crate::u32::bn::{BooleanNetwork, BooleanNetworkBuilder};
     let $1 = builder.make_variable("$1")
 
     ([a-z,A-Z,0-9,_]+)_focal :=
-    builder.update_function(&$1, Box::new(move |s| {}));
-
-     &
-     &&
+    builder.update_function(&$1, );
 
     ([a-z,A-Z,0-9,_]+) = 1
-    s.get(&$1)
+    var($1)
 
     ([a-z,A-Z,0-9,_]+) = 0
-    !s.get(&$1)
+    !var($1)
 */
 
 pub fn hspc_model() -> BooleanNetwork {
@@ -48,130 +46,130 @@ pub fn hspc_model() -> BooleanNetwork {
     let l_tlr = builder.make_variable("l_tlr");
 
     // Inputs:
-    builder.update_function(&cx43_m, Box::new(move |s| {
-        (s | cx43_m)
-    }));
-    builder.update_function(&l_tlr, Box::new(move |s| {
-        (s | l_tlr)
-    }));
+    builder.update_function(&cx43_m,
+        var(cx43_m)
+    );
+    builder.update_function(&l_tlr,
+        var(l_tlr)
+    );
 
     // Variables:
-    builder.update_function(&bcatenin_h, Box::new(move |s| {
-        (!(s | gsk3b_h))
-    }));
-    builder.update_function(&cxcr4_h, Box::new(move |s| {
-        (!(s | cxcr7_h)) && (!(s | gfi1_h)) && (s | cxcl12_m) && (!(s | gcsf))
-    }));
-    builder.update_function(&cxcr7_h, Box::new(move |s| {
-        (s | nfk_b_h) && (s | cxcl12_m)
-    }));
-    builder.update_function(&erk_h, Box::new(move |s| {
-        (!(s | cxcr4_h)) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (!(s | ros_h)) && (!(s | vla4_h)) && (s | gcsf) ||
-        (!(s | cxcr4_h)) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (!(s | ros_h)) && (s | vla4_h) ||
-        (!(s | cxcr4_h)) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (s | ros_h) ||
-        (!(s | cxcr4_h)) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (s | gfi1_h) && (!(s | gsk3b_h)) ||
-        (!(s | cxcr4_h)) && (s | cxcr7_h) && (!(s | fox_o3a_h)) && (!(s | gsk3b_h)) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (!(s | pi3kakt_h)) && (!(s | ros_h)) && (!(s | vla4_h)) && (s | gcsf) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (!(s | pi3kakt_h)) && (!(s | ros_h)) && (s | vla4_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (!(s | pi3kakt_h)) && (s | ros_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | gfi1_h)) && (!(s | gsk3b_h)) && (s | pi3kakt_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (s | gfi1_h) && (!(s | gsk3b_h)) ||
-        (s | cxcr4_h) && (s | cxcr7_h) && (!(s | fox_o3a_h)) && (!(s | gsk3b_h))
-    }));
-    builder.update_function(&fox_o3a_h, Box::new(move |s| {
-        (!(s | bcatenin_h)) && (!(s | erk_h)) && (!(s | pi3kakt_h)) && (s | ros_h) ||
-        (s | bcatenin_h) && (!(s | erk_h)) && (!(s | pi3kakt_h))
-    }));
-    builder.update_function(&gfi1_h, Box::new(move |s| {
-        (!(s | gfi1_h)) && (!(s | tlrs_h)) && (s | gcsf) ||
-        (!(s | gfi1_h)) && (s | tlrs_h)
-    }));
-    builder.update_function(&gsk3b_h, Box::new(move |s| {
-        (!(s | pi3kakt_h))
-    }));
-    builder.update_function(&nfk_b_h, Box::new(move |s| {
-        (!(s | fox_o3a_h)) && (!(s | pi3kakt_h)) && (!(s | ros_h)) && (s | tlrs_h) ||
-        (!(s | fox_o3a_h)) && (!(s | pi3kakt_h)) && (s | ros_h) ||
-        (!(s | fox_o3a_h)) && (s | pi3kakt_h) && (!(s | ros_h)) && (!(s | tlrs_h)) && (s | il1) ||
-        (!(s | fox_o3a_h)) && (s | pi3kakt_h) && (!(s | ros_h)) && (s | tlrs_h) ||
-        (!(s | fox_o3a_h)) && (s | pi3kakt_h) && (s | ros_h)
-    }));
-    builder.update_function(&pi3kakt_h, Box::new(move |s| {
-        (!(s | cxcr4_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (!(s | tlrs_h)) && (!(s | vla4_h)) && (s | gcsf) ||
-        (!(s | cxcr4_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (!(s | tlrs_h)) && (s | vla4_h) ||
-        (!(s | cxcr4_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (s | tlrs_h) ||
-        (!(s | cxcr4_h)) && (!(s | fox_o3a_h)) && (s | ros_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (!(s | tlrs_h)) && (!(s | vla4_h)) && (s | gcsf) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (!(s | tlrs_h)) && (s | vla4_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (!(s | ros_h)) && (s | tlrs_h) ||
-        (s | cxcr4_h) && (!(s | cxcr7_h)) && (!(s | fox_o3a_h)) && (s | ros_h) ||
-        (s | cxcr4_h) && (s | cxcr7_h) && (!(s | fox_o3a_h))
-    }));
-    builder.update_function(&ros_h, Box::new(move |s| {
-        (!(s | fox_o3a_h)) && (s | tlrs_h) && (s | il1)
-    }));
-    builder.update_function(&tlrs_h, Box::new(move |s| {
-        (s | l_tlr)
-    }));
-    builder.update_function(&vla4_h, Box::new(move |s| {
-        (s | cxcr4_h) && (s | vcam1_m)
-    }));
-    builder.update_function(&bcatenin_m, Box::new(move |s| {
-        (!(s | fox_o3a_m)) && (!(s | gsk3b_m)) && (!(s | nfk_b_m))
-    }));
-    builder.update_function(&cxcl12_m, Box::new(move |s| {
-        (s | cx43_m) && (!(s | bcatenin_m)) && (!(s | nfk_b_m)) && (!(s | gcsf))
-    }));
-    builder.update_function(&erk_m, Box::new(move |s| {
-        (!(s | ros_m)) && (!(s | tlrs_m)) && (s | gcsf) ||
-        (!(s | ros_m)) && (s | tlrs_m) ||
-        (s | ros_m)
-    }));
-    builder.update_function(&fox_o3a_m, Box::new(move |s| {
-        (!(s | bcatenin_m)) && (!(s | erk_m)) && (s | ros_m) && (!(s | pi3kakt_m)) ||
-        (s | bcatenin_m) && (!(s | erk_m)) && (!(s | pi3kakt_m))
-    }));
-    builder.update_function(&gsk3b_m, Box::new(move |s| {
-        (!(s | pi3kakt_m))
-    }));
-    builder.update_function(&nfk_b_m, Box::new(move |s| {
-        (!(s | erk_m)) && (!(s | pi3kakt_m)) && (s | tlrs_m) ||
-        (!(s | erk_m)) && (s | pi3kakt_m) && (!(s | tlrs_m)) && (s | il1) ||
-        (!(s | erk_m)) && (s | pi3kakt_m) && (s | tlrs_m) ||
-        (s | erk_m) && (!(s | ros_m)) && (!(s | pi3kakt_m)) && (s | tlrs_m) ||
-        (s | erk_m) && (!(s | ros_m)) && (s | pi3kakt_m) && (!(s | tlrs_m)) && (s | il1) ||
-        (s | erk_m) && (!(s | ros_m)) && (s | pi3kakt_m) && (s | tlrs_m) ||
-        (s | erk_m) && (s | ros_m)
-    }));
-    builder.update_function(&ros_m, Box::new(move |s| {
-        (!(s | fox_o3a_m)) && (s | tlrs_m) && (s | il1)
-    }));
-    builder.update_function(&pi3kakt_m, Box::new(move |s| {
-        (!(s | ros_m)) && (!(s | tlrs_m)) && (s | gcsf) ||
-        (!(s | ros_m)) && (s | tlrs_m) ||
-        (s | ros_m)
-    }));
-    builder.update_function(&tlrs_m, Box::new(move |s| {
-        (s | l_tlr)
-    }));
-    builder.update_function(&vcam1_m, Box::new(move |s| {
-        (!(s | bcatenin_m)) ||
-        (s | bcatenin_m) && (!(s | nfk_b_m)) && (s | pi3kakt_m) ||
-        (s | bcatenin_m) && (s | nfk_b_m)
-    }));
-    builder.update_function(&il1, Box::new(move |s| {
-        (!(s | nfk_b_h)) && (!(s | pi3kakt_h)) && (!(s | ros_h)) && (!(s | nfk_b_m)) && (s | ros_m) && (!(s | pi3kakt_m)) ||
-        (!(s | nfk_b_h)) && (!(s | pi3kakt_h)) && (!(s | ros_h)) && (s | nfk_b_m) && (!(s | pi3kakt_m)) ||
-        (!(s | nfk_b_h)) && (!(s | pi3kakt_h)) && (s | ros_h) ||
-        (!(s | nfk_b_h)) && (s | pi3kakt_h) && (!(s | nfk_b_m)) && (s | ros_m) && (!(s | pi3kakt_m)) ||
-        (!(s | nfk_b_h)) && (s | pi3kakt_h) && (s | nfk_b_m) && (!(s | pi3kakt_m)) ||
-        (s | nfk_b_h) && (!(s | pi3kakt_h)) ||
-        (s | nfk_b_h) && (s | pi3kakt_h) && (!(s | nfk_b_m)) && (s | ros_m) && (!(s | pi3kakt_m)) ||
-        (s | nfk_b_h) && (s | pi3kakt_h) && (s | nfk_b_m) && (!(s | pi3kakt_m))
-    }));
-    builder.update_function(&gcsf, Box::new(move |s| {
-        (s | il1)
-    }));
+    builder.update_function(&bcatenin_h,
+        !var(gsk3b_h)
+    );
+    builder.update_function(&cxcr4_h,
+        (!var(cxcr7_h)) & (!var(gfi1_h)) & var(cxcl12_m) & (!var(gcsf))
+    );
+    builder.update_function(&cxcr7_h,
+        var(nfk_b_h) & var(cxcl12_m)
+    );
+    builder.update_function(&erk_h,
+        (!var(cxcr4_h)) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & (!var(ros_h)) & (!var(vla4_h)) & var(gcsf) |
+        (!var(cxcr4_h)) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & (!var(ros_h)) & var(vla4_h) |
+        (!var(cxcr4_h)) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & var(ros_h) |
+        (!var(cxcr4_h)) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & var(gfi1_h) & (!var(gsk3b_h)) |
+        (!var(cxcr4_h)) & var(cxcr7_h) & (!var(fox_o3a_h)) & (!var(gsk3b_h)) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & (!var(pi3kakt_h)) & (!var(ros_h)) & (!var(vla4_h)) & var(gcsf) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & (!var(pi3kakt_h)) & (!var(ros_h)) & var(vla4_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & (!var(pi3kakt_h)) & var(ros_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(gfi1_h)) & (!var(gsk3b_h)) & var(pi3kakt_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & var(gfi1_h) & (!var(gsk3b_h)) |
+        var(cxcr4_h) & var(cxcr7_h) & (!var(fox_o3a_h)) & (!var(gsk3b_h))
+    );
+    builder.update_function(&fox_o3a_h,
+        (!var(bcatenin_h)) & (!var(erk_h)) & (!var(pi3kakt_h)) & var(ros_h) |
+        var(bcatenin_h) & (!var(erk_h)) & (!var(pi3kakt_h))
+    );
+    builder.update_function(&gfi1_h,
+        (!var(gfi1_h)) & (!var(tlrs_h)) & var(gcsf) |
+        (!var(gfi1_h)) & var(tlrs_h)
+    );
+    builder.update_function(&gsk3b_h,
+        !var(pi3kakt_h)
+    );
+    builder.update_function(&nfk_b_h,
+        (!var(fox_o3a_h)) & (!var(pi3kakt_h)) & (!var(ros_h)) & var(tlrs_h) |
+        (!var(fox_o3a_h)) & (!var(pi3kakt_h)) & var(ros_h) |
+        (!var(fox_o3a_h)) & var(pi3kakt_h) & (!var(ros_h)) & (!var(tlrs_h)) & var(il1) |
+        (!var(fox_o3a_h)) & var(pi3kakt_h) & (!var(ros_h)) & var(tlrs_h) |
+        (!var(fox_o3a_h)) & var(pi3kakt_h) & var(ros_h)
+    );
+    builder.update_function(&pi3kakt_h,
+        (!var(cxcr4_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & (!var(tlrs_h)) & (!var(vla4_h)) & var(gcsf) |
+        (!var(cxcr4_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & (!var(tlrs_h)) & var(vla4_h) |
+        (!var(cxcr4_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & var(tlrs_h) |
+        (!var(cxcr4_h)) & (!var(fox_o3a_h)) & var(ros_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & (!var(tlrs_h)) & (!var(vla4_h)) & var(gcsf) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & (!var(tlrs_h)) & var(vla4_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & (!var(ros_h)) & var(tlrs_h) |
+        var(cxcr4_h) & (!var(cxcr7_h)) & (!var(fox_o3a_h)) & var(ros_h) |
+        var(cxcr4_h) & var(cxcr7_h) & (!var(fox_o3a_h))
+    );
+    builder.update_function(&ros_h,
+        (!var(fox_o3a_h)) & var(tlrs_h) & var(il1)
+    );
+    builder.update_function(&tlrs_h,
+        var(l_tlr)
+    );
+    builder.update_function(&vla4_h,
+        var(cxcr4_h) & var(vcam1_m)
+    );
+    builder.update_function(&bcatenin_m,
+        (!var(fox_o3a_m)) & (!var(gsk3b_m)) & (!var(nfk_b_m))
+    );
+    builder.update_function(&cxcl12_m,
+        var(cx43_m) & (!var(bcatenin_m)) & (!var(nfk_b_m)) & (!var(gcsf))
+    );
+    builder.update_function(&erk_m,
+        (!var(ros_m)) & (!var(tlrs_m)) & var(gcsf) |
+        (!var(ros_m)) & var(tlrs_m) |
+        var(ros_m)
+    );
+    builder.update_function(&fox_o3a_m,
+        (!var(bcatenin_m)) & (!var(erk_m)) & var(ros_m) & (!var(pi3kakt_m)) |
+        var(bcatenin_m) & (!var(erk_m)) & (!var(pi3kakt_m))
+    );
+    builder.update_function(&gsk3b_m,
+        !var(pi3kakt_m)
+    );
+    builder.update_function(&nfk_b_m,
+        (!var(erk_m)) & (!var(pi3kakt_m)) & var(tlrs_m) |
+        (!var(erk_m)) & var(pi3kakt_m) & (!var(tlrs_m)) & var(il1) |
+        (!var(erk_m)) & var(pi3kakt_m) & var(tlrs_m) |
+        var(erk_m) & (!var(ros_m)) & (!var(pi3kakt_m)) & var(tlrs_m) |
+        var(erk_m) & (!var(ros_m)) & var(pi3kakt_m) & (!var(tlrs_m)) & var(il1) |
+        var(erk_m) & (!var(ros_m)) & var(pi3kakt_m) & var(tlrs_m) |
+        var(erk_m) & var(ros_m)
+    );
+    builder.update_function(&ros_m,
+        (!var(fox_o3a_m)) & var(tlrs_m) & var(il1)
+    );
+    builder.update_function(&pi3kakt_m,
+        (!var(ros_m)) & (!var(tlrs_m)) & var(gcsf) |
+        (!var(ros_m)) & var(tlrs_m) |
+        var(ros_m)
+    );
+    builder.update_function(&tlrs_m,
+        var(l_tlr)
+    );
+    builder.update_function(&vcam1_m,
+        (!var(bcatenin_m)) |
+        var(bcatenin_m) & (!var(nfk_b_m)) & var(pi3kakt_m) |
+        var(bcatenin_m) & var(nfk_b_m)
+    );
+    builder.update_function(&il1,
+        (!var(nfk_b_h)) & (!var(pi3kakt_h)) & (!var(ros_h)) & (!var(nfk_b_m)) & var(ros_m) & (!var(pi3kakt_m)) |
+        (!var(nfk_b_h)) & (!var(pi3kakt_h)) & (!var(ros_h)) & var(nfk_b_m) & (!var(pi3kakt_m)) |
+        (!var(nfk_b_h)) & (!var(pi3kakt_h)) & var(ros_h) |
+        (!var(nfk_b_h)) & var(pi3kakt_h) & (!var(nfk_b_m)) & var(ros_m) & (!var(pi3kakt_m)) |
+        (!var(nfk_b_h)) & var(pi3kakt_h) & var(nfk_b_m) & (!var(pi3kakt_m)) |
+        var(nfk_b_h) & (!var(pi3kakt_h)) |
+        var(nfk_b_h) & var(pi3kakt_h) & (!var(nfk_b_m)) & var(ros_m) & (!var(pi3kakt_m)) |
+        var(nfk_b_h) & var(pi3kakt_h) & var(nfk_b_m) & (!var(pi3kakt_m))
+    );
+    builder.update_function(&gcsf,
+        var(il1)
+    );
 
     return builder.build_network();
 }
@@ -209,119 +207,119 @@ pub fn t2dm_model() -> BooleanNetwork {
     let therapy = builder.make_variable("therapy");
 
     // Inputs:
-    builder.update_function(&insulin, Box::new(move |s| {
-        (s | insulin)
-    }));
-    builder.update_function(&gf, Box::new(move |s| {
-        (s | gf)
-    }));
-    builder.update_function(&therapy, Box::new(move |s| {
-        (s | therapy)
-    }));
+    builder.update_function(&insulin,
+        var(insulin)
+    );
+    builder.update_function(&gf,
+        var(gf)
+    );
+    builder.update_function(&therapy,
+        var(therapy)
+    );
 
     // Variables:
-    builder.update_function(&senescence, Box::new(move |s| {
-        (!(s | p16)) && (s | p21) && (s | m_torc1_s6k1) || (s | p16)
-    }));
-
-    builder.update_function(&g1_s, Box::new(move |s| {
-        (!(s | p21)) && (s | cdk2) && (s | e2f1) && (s | metabolism)
-    }));
-
-    builder.update_function(&mapk, Box::new(move |s| {
-        (s | gf) && (!(s | pp2a))
-    }));
-
-    builder.update_function(&p16, Box::new(move |s| {
-        (s | mapk) && (!(s | p53)) && (!(s | e2f1)) && (!(s | prc))
-    }));
-
-    builder.update_function(&mdm2, Box::new(move |s| {
-        ((!(s | p16)) && (!(s | p53)) && (s | akt) && (!(s | m_torc1_s6k1)) && (!(s | myc)) && (!(s | e2f1))) ||
-            ((!(s | p16)) && (s | p53) && (!(s | m_torc1_s6k1)) && (!(s | myc)) && (!(s | e2f1))) ||
-            ((s | p16) && (!(s | m_torc1_s6k1)) && (!(s | myc)) && (!(s | e2f1)))
-    }));
-
-    builder.update_function(&p53, Box::new(move |s| {
-        (!(s | mdm2))
-    }));
-
-    builder.update_function(&p21, Box::new(move |s| {
-        ((!(s | p53)) && (!(s | akt)) && (!(s | myc)) && (s | foxo)) ||
-            ((s | p53) && (!(s | akt)) && (!(s | myc)))
-    }));
-
-    builder.update_function(&akt, Box::new(move |s| {
-        ((!(s | irs_pik3ca)) && (!(s | pten)) && (s | cdk2) && (!(s | pp2a))) ||
-            ((s | irs_pik3ca) && (!(s | pten)) && (!(s | pp2a)))
-    }));
-
-    builder.update_function(&m_torc1_s6k1, Box::new(move |s| {
-        (!(s | ampk)) && (!(s | tsc))
-    }));
-
-    builder.update_function(&atp, Box::new(move |s| {
-        (s | metabolism)
-    }));
-
-    builder.update_function(&irs_pik3ca, Box::new(move |s| {
-        (s | insulin) && (!(s | m_torc1_s6k1))
-    }));
-
-    builder.update_function(&ampk, Box::new(move |s| {
-        (s | p53) && (!(s | atp))
-    }));
-
-    builder.update_function(&pten, Box::new(move |s| {
-        (s | p53) && (!(s | akt))
-    }));
-
-    builder.update_function(&tsc, Box::new(move |s| {
-        (!(s | mapk)) && (!(s | akt)) && (s | ampk)
-    }));
-
-    builder.update_function(&myc, Box::new(move |s| {
-        (s | mapk) && (!(s | p53)) && (s | m_torc1_s6k1) && (s | e2f1)
-    }));
-
-    builder.update_function(&cdk2, Box::new(move |s| {
-        ((!(s | p21)) && (s | m_torc1_s6k1) && (!(s | myc)) && (s | e2f1)) ||
-            ((!(s | p21)) && (s | m_torc1_s6k1) && (s | myc))
-    }));
-
-    builder.update_function(&p_rb, Box::new(move |s| {
-        (!(s | cdk2))
-    }));
-
-    builder.update_function(&e2f1, Box::new(move |s| {
-        ((!(s | gf)) && (s | myc) && (!(s | p_rb)) && (s | e2f1)) ||
-            ((s | gf) && (!(s | p_rb)) && (s | e2f1))
-    }));
-
-    builder.update_function(&prc, Box::new(move |s| {
-        (!(s | akt)) && (s | myc)
-    }));
-
-    builder.update_function(&metabolism, Box::new(move |s| {
-        ((!(s | mapk)) && (!(s | akt)) && (s | m_torc1_s6k1) && (s | pp1c)) ||
-            ((!(s | mapk)) && (s | akt) && (s | m_torc1_s6k1)) ||
-            ((s | mapk) && (!(s | akt)) && (s | pp1c)) ||
-            ((s | mapk) && (s | akt))
-    }));
-
-    builder.update_function(&pp2a, Box::new(move |s| {
-        (!(s | m_torc1_s6k1))
-    }));
-
-    builder.update_function(&foxo, Box::new(move |s| {
-        ((!(s | mapk)) && (!(s | p16)) && (!(s | akt)) && (!(s | ampk)) && (s | metabolism)) ||
-            ((!(s | mapk)) && (!(s | p16)) && (!(s | akt)) && (s | ampk)) ||
-            ((!(s | mapk)) && (s | p16) && (!(s | akt)))
-    }));
-
-    builder.update_function(&pp1c, Box::new(move |s| {
-        ((!(s | mapk)) && (s | akt)) || (s | mapk)
-    }));
+    builder.update_function(&senescence,
+        (!var(p16)) & var(p21) & var(m_torc1_s6k1) | var(p16)
+    );
+
+    builder.update_function(&g1_s,
+        (!var(p21)) & var(cdk2) & var(e2f1) & var(metabolism)
+    );
+
+    builder.update_function(&mapk,
+        var(gf) & (!var(pp2a))
+    );
+
+    builder.update_function(&p16,
+        var(mapk) & (!var(p53)) & (!var(e2f1)) & (!var(prc))
+    );
+
+    builder.update_function(&mdm2,
+        ((!var(p16)) & (!var(p53)) & var(akt) & (!var(m_torc1_s6k1)) & (!var(myc)) & (!var(e2f1))) |
+            ((!var(p16)) & var(p53) & (!var(m_torc1_s6k1)) & (!var(myc)) & (!var(e2f1))) |
+            (var(p16) & (!var(m_torc1_s6k1)) & (!var(myc)) & (!var(e2f1)))
+    );
+
+    builder.update_function(&p53,
+        !var(mdm2)
+    );
+
+    builder.update_function(&p21,
+        ((!var(p53)) & (!var(akt)) & (!var(myc)) & var(foxo)) |
+            (var(p53) & (!var(akt)) & (!var(myc)))
+    );
+
+    builder.update_function(&akt,
+        ((!var(irs_pik3ca)) & (!var(pten)) & var(cdk2) & (!var(pp2a))) |
+            (var(irs_pik3ca) & (!var(pten)) & (!var(pp2a)))
+    );
+
+    builder.update_function(&m_torc1_s6k1,
+        (!var(ampk)) & (!var(tsc))
+    );
+
+    builder.update_function(&atp,
+        var(metabolism)
+    );
+
+    builder.update_function(&irs_pik3ca,
+        var(insulin) & (!var(m_torc1_s6k1))
+    );
+
+    builder.update_function(&ampk,
+        var(p53) & (!var(atp))
+    );
+
+    builder.update_function(&pten,
+        var(p53) & (!var(akt))
+    );
+
+    builder.update_function(&tsc,
+        (!var(mapk)) & (!var(akt)) & var(ampk)
+    );
+
+    builder.update_function(&myc,
+        var(mapk) & (!var(p53)) & var(m_torc1_s6k1) & var(e2f1)
+    );
+
+    builder.update_function(&cdk2,
+        ((!var(p21)) & var(m_torc1_s6k1) & (!var(myc)) & var(e2f1)) |
+            ((!var(p21)) & var(m_torc1_s6k1) & var(myc))
+    );
+
+    builder.update_function(&p_rb,
+        !var(cdk2)
+    );
+
+    builder.update_function(&e2f1,
+        ((!var(gf)) & var(myc) & (!var(p_rb)) & var(e2f1)) |
+            (var(gf) & (!var(p_rb)) & var(e2f1))
+    );
+
+    builder.update_function(&prc,
+        (!var(akt)) & var(myc)
+    );
+
+    builder.update_function(&metabolism,
+        ((!var(mapk)) & (!var(akt)) & var(m_torc1_s6k1) & var(pp1c)) |
+            ((!var(mapk)) & var(akt) & var(m_torc1_s6k1)) |
+            (var(mapk) & (!var(akt)) & var(pp1c)) |
+            (var(mapk) & var(akt))
+    );
+
+    builder.update_function(&pp2a,
+        !var(m_torc1_s6k1)
+    );
+
+    builder.update_function(&foxo,
+        ((!var(mapk)) & (!var(p16)) & (!var(akt)) & (!var(ampk)) & var(metabolism)) |
+            ((!var(mapk)) & (!var(p16)) & (!var(akt)) & var(ampk)) |
+            ((!var(mapk)) & var(p16) & (!var(akt)))
+    );
+
+    builder.update_function(&pp1c,
+        ((!var(mapk)) & var(akt)) | var(mapk)
+    );
 
     return builder.build_network();
 }
@@ -352,98 +350,96 @@ pub fn erb_b2_model() -> BooleanNetwork {
     let akt1 = builder.make_variable("akt1");
     let mek1 = builder.make_variable("mek1");
 
-    builder.update_function(&mek1, Box::new(move |s| {
-        ((!(s | igf1r)) && (!(s | erbb1)) && (!(s | erbb2_3)) && (!(s | erbb1_2)) && (s | erbb1_3)) ||
-            ((!(s | igf1r)) && (!(s | erbb1)) && (!(s | erbb2_3)) && (s | erbb1_2)) ||
-            ((!(s | igf1r)) && (!(s | erbb1)) && (s | erbb2_3)) ||
-            ((!(s | igf1r)) && (s | erbb1)) ||
-            (s | igf1r)
-    }));
-
-    builder.update_function(&cdk4, Box::new(move |s| {
-        (!(s | p21)) && (s | cyclin_d1) && (!(s | p27))
-    }));
-
-    builder.update_function(&p21, Box::new(move |s| {
-        (!(s | cdk4)) && (!(s | akt1)) && (!(s | myc))
-    }));
-
-    builder.update_function(&cyclin_d1, Box::new(move |s| {
-        ((!(s | mek1)) && (s | eralpha) && (s | akt1) && (s | myc)) ||
-            ((s | mek1) && (s | eralpha) && (s | myc))
-    }));
-
-    builder.update_function(&p27, Box::new(move |s| {
-        (!(s | cdk4)) && (!(s | cdk2)) && (!(s | akt1)) && (!(s | myc))
-    }));
-
-    builder.update_function(&cdk2, Box::new(move |s| {
-        (!(s | p21)) && (!(s | p27)) && (s | cyclin_e1)
-    }));
-
-    builder.update_function(&cyclin_e1, Box::new(move |s| {
-        (s | myc)
-    }));
-
-    builder.update_function(&p_rb1, Box::new(move |s| {
-        (s | cdk4) && (s | cdk6)
-    }));
-
-    builder.update_function(&eralpha, Box::new(move |s| {
-        ((!(s | mek1)) && (s | akt1)) ||
-            (s | mek1)
-    }));
-
-    builder.update_function(&akt1, Box::new(move |s| {
-        ((!(s | igf1r)) && (!(s | erbb1)) && (!(s | erbb2_3)) && (!(s | erbb1_2)) && (s | erbb1_3)) ||
-            ((!(s | igf1r)) && (!(s | erbb1)) && (!(s | erbb2_3)) && (s | erbb1_2)) ||
-            ((!(s | igf1r)) && (!(s | erbb1)) && (s | erbb2_3)) ||
-            ((!(s | igf1r)) && (s | erbb1)) ||
-            (s | igf1r)
-    }));
-
-    builder.update_function(&cdk6, Box::new(move |s| {
-        (s | cyclin_d1)
-    }));
-
-    builder.update_function(&myc, Box::new(move |s| {
-        ((!(s | mek1)) && (!(s | eralpha)) && (s | akt1)) ||
-            ((!(s | mek1)) && (s | eralpha)) ||
-            (s | mek1)
-    }));
-
-    builder.update_function(&igf1r, Box::new(move |s| {
-        ((!(s | eralpha)) && (s | akt1) && (!(s | erbb2_3))) ||
-            ((s | eralpha) && (!(s | erbb2_3)))
-    }));
-
-    builder.update_function(&egf, Box::new(move |_| {
-        true
-    }));
-
-    builder.update_function(&erbb1, Box::new(move |s| {
-        (s | egf)
-    }));
-
-    builder.update_function(&erbb2, Box::new(move |s| {
-        (s | egf)
-    }));
-
-    builder.update_function(&erbb3, Box::new(move |s| {
-        (s | egf)
-    }));
-
-    builder.update_function(&erbb2_3, Box::new(move |s| {
-        (s | erbb2) && (s | erbb3)
-    }));
-
-    builder.update_function(&erbb1_2, Box::new(move |s| {
-        (s | erbb1) && (s | erbb2)
-    }));
-
-    builder.update_function(&erbb1_3, Box::new(move |s| {
-        (s | erbb1) && (s | erbb3)
-    }));
+    builder.update_function(&mek1,
+        ((!var(igf1r)) & (!var(erbb1)) & (!var(erbb2_3)) & (!var(erbb1_2)) & var(erbb1_3)) |
+            ((!var(igf1r)) & (!var(erbb1)) & (!var(erbb2_3)) & var(erbb1_2)) |
+            ((!var(igf1r)) & (!var(erbb1)) & var(erbb2_3)) |
+            ((!var(igf1r)) & var(erbb1)) |
+            var(igf1r)
+    );
+
+    builder.update_function(&cdk4,
+        (!var(p21)) & var(cyclin_d1) & (!var(p27))
+    );
+
+    builder.update_function(&p21,
+        (!var(cdk4)) & (!var(akt1)) & (!var(myc))
+    );
+
+    builder.update_function(&cyclin_d1,
+        ((!var(mek1)) & var(eralpha) & var(akt1) & var(myc)) |
+            (var(mek1) & var(eralpha) & var(myc))
+    );
+
+    builder.update_function(&p27,
+        (!var(cdk4)) & (!var(cdk2)) & (!var(akt1)) & (!var(myc))
+    );
+
+    builder.update_function(&cdk2,
+        (!var(p21)) & (!var(p27)) & var(cyclin_e1)
+    );
+
+    builder.update_function(&cyclin_e1,
+        var(myc)
+    );
+
+    builder.update_function(&p_rb1,
+        var(cdk4) & var(cdk6)
+    );
+
+    builder.update_function(&eralpha,
+        ((!var(mek1)) & var(akt1)) |
+            var(mek1)
+    );
+
+    builder.update_function(&akt1,
+        ((!var(igf1r)) & (!var(erbb1)) & (!var(erbb2_3)) & (!var(erbb1_2)) & var(erbb1_3)) |
+            ((!var(igf1r)) & (!var(erbb1)) & (!var(erbb2_3)) & var(erbb1_2)) |
+            ((!var(igf1r)) & (!var(erbb1)) & var(erbb2_3)) |
+            ((!var(igf1r)) & var(erbb1)) |
+            var(igf1r)
+    );
+
+    builder.update_function(&cdk6,
+        var(cyclin_d1)
+    );
+
+    builder.update_function(&myc,
+        ((!var(mek1)) & (!var(eralpha)) & var(akt1)) |
+            ((!var(mek1)) & var(eralpha)) |
+            var(mek1)
+    );
+
+    builder.update_function(&igf1r,
+        ((!var(eralpha)) & var(akt1) & (!var(erbb2_3))) |
+            (var(eralpha) & (!var(erbb2_3)))
+    );
+
+    builder.update_function(&egf, BooleanExpression::Const(true));
+
+    builder.update_function(&erbb1,
+        var(egf)
+    );
+
+    builder.update_function(&erbb2,
+        var(egf)
+    );
+
+    builder.update_function(&erbb3,
+        var(egf)
+    );
+
+    builder.update_function(&erbb2_3,
+        var(erbb2) & var(erbb3)
+    );
+
+    builder.update_function(&erbb1_2,
+        var(erbb1) & var(erbb2)
+    );
+
+    builder.update_function(&erbb1_3,
+        var(erbb1) & var(erbb3)
+    );
 
 
     return builder.build_network();
@@ -456,18 +452,18 @@ pub fn demo_model_disorder() -> BooleanNetwork {
     let dna = builder.make_variable("DNA");
     let m2c = builder.make_variable("M2C");
     let m2n = builder.make_variable("M2N");
-    builder.update_function(&p53, Box::new(move |s| {
-        !(s | m2n)
-    }));
-    builder.update_function(&m2c, Box::new(move |s| {
-        (s | p53)
-    }));
-    builder.update_function(&dna, Box::new(move |s| {
-        !(s | p53)
-    }));
-    builder.update_function(&m2n, Box::new(move |s| {
-        (s | m2c) || (!(s | m2c) && !(s | dna) && !(s | p53))
-    }));
+    builder.update_function(&p53,
+        !var(m2n)
+    );
+    builder.update_function(&m2c,
+        var(p53)
+    );
+    builder.update_function(&dna,
+        !var(p53)
+    );
+    builder.update_function(&m2n,
+        var(m2c) | (!var(m2c) & !var(dna) & !var(p53))
+    );
 
     return builder.build_network();
 }
@@ -478,18 +474,18 @@ pub fn demo_model_oscillation() -> BooleanNetwork {
     let dna = builder.make_variable("DNA");
     let m2c = builder.make_variable("M2C");
     let m2n = builder.make_variable("M2N");
-    builder.update_function(&p53, Box::new(move |s| {
-        !(s | m2n)
-    }));
-    builder.update_function(&m2c, Box::new(move |s| {
-        (s | p53)
-    }));
-    builder.update_function(&dna, Box::new(move |s| {
-        !(s | p53) || (s | dna)
-    }));
-    builder.update_function(&m2n, Box::new(move |s| {
-        (s | m2c) || (!(s | m2c) && !(s |  dna) && !(s | p53))
-    }));
+    builder.update_function(&p53,
+        !var(m2n)
+    );
+    builder.update_function(&m2c,
+        var(p53)
+    );
+    builder.update_function(&dna,
+        !var(p53) | var(dna)
+    );
+    builder.update_function(&m2n,
+        var(m2c) | (!var(m2c) & !var(dna) & !var(p53))
+    );
 
     return builder.build_network();
 }
@@ -500,18 +496,18 @@ pub fn demo_model_bistable() -> BooleanNetwork {
     let dna = builder.make_variable("DNA");
     let m2c = builder.make_variable("M2C");
     let m2n = builder.make_variable("M2N");
-    builder.update_function(&p53, Box::new(move |s| {
-        !(s | m2n)
-    }));
-    builder.update_function(&m2c, Box::new(move |s| {
-        (s | p53)
-    }));
-    builder.update_function(&dna, Box::new(move |s| {
-        !(s | p53) || (s | dna)
-    }));
-    builder.update_function(&m2n, Box::new(move |s| {
-        ((s | m2c) && (!(s | dna) || !(s | p53))) || (!(s | m2c) && !(s | p53))
-    }));
+    builder.update_function(&p53,
+        !var(m2n)
+    );
+    builder.update_function(&m2c,
+        var(p53)
+    );
+    builder.update_function(&dna,
+        !var(p53) | var(dna)
+    );
+    builder.update_function(&m2n,
+        (var(m2c) & (!var(dna) | !var(p53))) | (!var(m2c) & !var(p53))
+    );
 
     return builder.build_network();
 }
@@ -522,18 +518,18 @@ pub fn demo_model_stable() -> BooleanNetwork {
     let dna = builder.make_variable("DNA");
     let m2c = builder.make_variable("M2C");
     let m2n = builder.make_variable("M2N");
-    builder.update_function(&p53, Box::new(move |s| {
-        !(s | m2n)
-    }));
-    builder.update_function(&m2c, Box::new(move |s| {
-        (s | p53)
-    }));
-    builder.update_function(&dna, Box::new(move |s| {
-        !(s | p53) || (s | dna)
-    }));
-    builder.update_function(&m2n, Box::new(move |s| {
-        ((s | m2c) && (!(s | dna) || !(s | p53))) || (!(s | m2c) && !(s | dna) && !(s | p53))
-    }));
+    builder.update_function(&p53,
+        !var(m2n)
+    );
+    builder.update_function(&m2c,
+        var(p53)
+    );
+    builder.update_function(&dna,
+        !var(p53) | var(dna)
+    );
+    builder.update_function(&m2n,
+        (var(m2c) & (!var(dna) | !var(p53))) | (!var(m2c) & !var(dna) & !var(p53))
+    );
 
     return builder.build_network();
 }
\ No newline at end of file
@@ -1,13 +1,15 @@
 use std::sync::atomic::{AtomicU32, Ordering};
 use rand::prelude::StdRng;
 use rand::{RngCore, SeedableRng};
-use crate::u32::bn::{StateId, BooleanNetwork, VariableIterator};
-use crate::u32::sequential::{DisjointSets, DEAD, FRESH};
+use crate::u32::bn::{StateId, BooleanNetwork, VariableIterator, StateIterator, AsyncNetwork};
+use crate::u32::sequential::{DisjointSets, DEAD, FRESH, scc};
 use crossbeam::thread;
 use crate::bitset::AtomicBitSet;
+use crate::u32::decomposition::SccDecomposition;
 
-
-pub fn parallel_scc(network: &BooleanNetwork, parallelism: u32) {
+/// Compute the SCC decomposition of the asynchronous state space of the [network]
+/// using the given number of threads.
+pub fn parallel_scc(network: &BooleanNetwork, parallelism: u32) -> SccDecomposition {
 
     //let global_network = Arc::new(network);
     let global_sets = AtomicDisjointSets::new(network.state_count() as usize, 1234567890);
@@ -19,113 +21,162 @@ pub fn parallel_scc(network: &BooleanNetwork, parallelism: u32) {
     thread::scope(|thread_scope| {
         for _ in 0..parallelism {
             thread_scope.spawn(|_| {
+                let thread_id: u64 = thread_id.fetch_add(1, Ordering::SeqCst) as u64;
+                let mut worker = SccWorker::new(network, &global_sets, &global_dead, thread_id, parallelism);
+                while worker.step() {}
+                println!("Processed {} states in {} iterations and max stack {}", worker.explored, worker.iter, worker.max_stack_size)
+            });
+        }
+    }).unwrap();
 
-                let mut sets = DisjointSets::new(network.state_count() as usize, 1234567890);
-                let mut stack: Vec<(StateId, VariableIterator)> = Vec::new();
+    return SccDecomposition::from_partition(network, |s| global_sets.find_root(s));
+}
 
-                let thread_id: u64 = thread_id.fetch_add(1, Ordering::SeqCst) as u64;
-                let key: u64 = thread_id * (network.state_count() / (parallelism as u64));
-
-                let mut explored: usize = 0;
-                let mut iter: usize = 0;
-                let mut max_stack_size: usize = 0;
-
-                for root_seq in network.states() {
-                    let root = StateId { value: ((root_seq.value as u64 + key) % network.state_count()) as u32 };
-                    if sets.get_payload(&root) == DEAD { continue }
-                    let set_of_root = global_sets.find_root(&root);
-                    if global_dead.is_set(set_of_root) { continue }
-
-                    explored += 1;
-                    //if thread_id == 0 {
-                    //    print!("\rRemaining {}                             ", network.state_count() - root.value as u64);
-                    //}
-
-                    sets.set_payload(&root, 0);
-                    stack.push((root, network.variables()));
-
-                    while let Some((s, it)) = stack.last_mut() {
-                        iter += 1;
-                        let set_of_s = global_sets.find_root(&s);
-                        if global_dead.is_set(set_of_s) {
-                            stack.pop();
-                        } else {
-                            if let Some(var) = it.next() {
-                                // if this variable has no successor or the successor SCC is already dead, do nothing
-                                if let Some(t) = network.successor(&s, &var) {
-                                    // Note that we can't test if t is dead (it can be a dead part of otherwise
-                                    // unfinished component), it root(t) is dead (the same) and if we didn't have
-                                    // special value for DEAD payload, we wouldn't know if the returned stack root
-                                    // index is valid because it can popped (and invalid) or overwritten by
-                                    // something else.
-                                    let set_of_t = global_sets.find_root(&t);
-                                    let payload = sets.get_payload(&t);
-                                    if payload == FRESH && !global_dead.is_set(set_of_t) {
-                                        explored += 1;
-                                        // t is newly discovered - add it to the stack!
-                                        sets.set_payload(&t, stack.len() as u32);
-                                        stack.push((t, network.variables()));
-                                        if stack.len() > max_stack_size {
-                                            max_stack_size = stack.len()
-                                        }
-                                        // this has no performance impact since the branch is easy to predict...
-                                        if stack.len() as u32 == DEAD { panic!("Stack overflow!") }
-                                    } else if /*payload != DEAD &&*/ !global_dead.is_set(set_of_t) {
-                                        // t is already visited, but not dead, meaning we found a cycle.
-                                        // Now we have to merge everything on the stack with t, but skip
-                                        // the already merged parts of the graph using the stack_bottom
-                                        // pointers.
-                                        let mut to_merge_index = stack.len() - 1;
-                                        while sets.find_root(&stack[to_merge_index].0) != sets.find_root(&t) {
-                                            // skip all items already in the same set
-                                            to_merge_index = sets.get_payload(&stack[to_merge_index].0) as usize;
-                                            // union them with t
-                                            sets.union(stack[to_merge_index].0, t);
-                                            global_sets.union(stack[to_merge_index].0, t);
-                                            // and then move one item lower
-                                            to_merge_index -= 1;    // "virtual" pop
-                                        }
-                                    }
-                                }
-                            } else {
-                                // State is fully explored and can be removed from the stack
-                                let (s, _) = stack.pop().unwrap();                     // pop first to acquire ownership
-                                if sets.get_payload(&s) as usize == stack.len() {     // + 1 for the already popped element
-                                    // found component!
-                                    sets.set_payload(&s, DEAD);
-                                    let set_of_s = sets.find_root(&s);
-                                    global_dead.set(set_of_s);  // set globally dead
-                                }
-                            }
-
-                        }
-                    }
-                    // reset stacks for next iteration
-                    stack.clear();
-                }
 
-                //if thread_id == 0 {
-                //    print!("\r");
-                //}
+/// Compute the SCC decomposition using [parallel_scc] and validate it against the
+/// sequential [scc] algorithm. Panics if the two partitions differ.
+pub fn parallel_scc_validated(network: &BooleanNetwork, parallelism: u32) -> SccDecomposition {
+    let parallel = parallel_scc(network, parallelism);
+    let sequential = scc(network);
+    if parallel != sequential {
+        let mismatch = network.states()
+            .find(|s| parallel.component_of(s) != sequential.component_of(s))
+            .unwrap();
+        panic!("Parallel SCC decomposition differs from sequential decomposition in state {} ({} vs. {}).",
+               network.format_state(&mismatch), parallel.component_of(&mismatch), sequential.component_of(&mismatch));
+    }
+    return parallel;
+}
 
-                println!("Processed {} states in {} iterations and max stack {}", explored, iter, max_stack_size)
-            });
+/// Search state of one thread of [parallel_scc]. The search is performed step by step using
+/// [SccWorker::step], so that several workers can also be interleaved deterministically.
+struct SccWorker<'a> {
+    network: &'a BooleanNetwork,
+    global_sets: &'a AtomicDisjointSets,
+    global_dead: &'a AtomicBitSet,
+    sets: DisjointSets,
+    stack: Vec<(StateId, VariableIterator)>,
+    roots: StateIterator,
+    // every worker starts the searches from a different part of the state space
+    key: u64,
+    explored: usize,
+    iter: usize,
+    max_stack_size: usize
+}
+
+impl SccWorker<'_> {
+
+    fn new<'a>(
+        network: &'a BooleanNetwork, global_sets: &'a AtomicDisjointSets, global_dead: &'a AtomicBitSet,
+        thread_id: u64, parallelism: u32
+    ) -> SccWorker<'a> {
+        return SccWorker {
+            network, global_sets, global_dead,
+            sets: DisjointSets::new(network.state_count() as usize, 1234567890),
+            stack: Vec::new(),
+            roots: network.states(),
+            key: thread_id * (network.state_count() / (parallelism as u64)),
+            explored: 0,
+            iter: 0,
+            max_stack_size: 0
         }
-    }).unwrap();
+    }
+
+    /// Perform one step of the search: either start a new search from the next root, or
+    /// explore one edge of the state on top of the stack. Returns false once all roots
+    /// have been processed.
+    fn step(&mut self) -> bool {
+        let network = self.network;
+        let global_sets = self.global_sets;
+        let global_dead = self.global_dead;
+        let sets = &mut self.sets;
+        let stack = &mut self.stack;
+
+        let (s, it) = match stack.last_mut() {
+            Some(top) => top,
+            None => return self.start_search()
+        };
+        self.iter += 1;
+        let set_of_s = global_sets.find_root(s);
+        if global_dead.is_set(set_of_s) {
+            stack.pop();
+        } else if let Some(var) = it.next() {
+            // if this variable has no successor or the successor SCC is already dead, do nothing
+            if let Some(t) = network.successor(s, &var) {
+                // Note that we can't test if t is dead (it can be a dead part of otherwise
+                // unfinished component), it root(t) is dead (the same) and if we didn't have
+                // special value for DEAD payload, we wouldn't know if the returned stack root
+                // index is valid because it can popped (and invalid) or overwritten by
+                // something else.
+                let set_of_t = global_sets.find_root(&t);
+                let payload = sets.get_payload(&t);
+                // Components finished by this worker are always globally dead (see below), so
+                // a DEAD payload is always caught by the global check as well. The payload check
+                // in the branches below only guards against using DEAD as a stack index.
+                debug_assert!(payload != DEAD || global_dead.is_set(set_of_t));
+                if payload == FRESH && !global_dead.is_set(set_of_t) {
+                    self.explored += 1;
+                    // t is newly discovered - add it to the stack!
+                    sets.set_payload(&t, stack.len() as u32);
+                    stack.push((t, network.variables()));
+                    if stack.len() > self.max_stack_size {
+                        self.max_stack_size = stack.len()
+                    }
+                    // this has no performance impact since the branch is easy to predict...
+                    if stack.len() as u32 == DEAD { panic!("Stack overflow!") }
+                } else if payload != DEAD && !global_dead.is_set(set_of_t) {
+                    // t is already visited, but not dead, meaning we found a cycle.
+                    // Now we have to merge everything on the stack with t, but skip
+                    // the already merged parts of the graph using the stack_bottom
+                    // pointers.
+                    let mut to_merge_index = stack.len() - 1;
+                    while sets.find_root(&stack[to_merge_index].0) != sets.find_root(&t) {
+                        // skip all items already in the same set
+                        to_merge_index = sets.get_payload(&stack[to_merge_index].0) as usize;
+                        // union them with t
+                        sets.union(stack[to_merge_index].0, t);
+                        global_sets.union(stack[to_merge_index].0, t);
+                        // and then move one item lower
+                        to_merge_index -= 1;    // "virtual" pop
+                    }
+                }
+            }
+        } else {
+            // State is fully explored and can be removed from the stack
+            let (s, _) = stack.pop().unwrap();                     // pop first to acquire ownership
+            if sets.get_payload(&s) as usize == stack.len() {     // + 1 for the already popped element
+                // found component!
+                sets.set_payload(&s, DEAD);
+                // Every local union is also performed globally, hence the global set of s is
+                // now the whole component and its root cannot change anymore. Both structures
+                // use the same hash mask, so the root is the element of the component with the
+                // largest hash, which makes the global and local root the same state. The
+                // global root is used since that is what other workers test.
+                let set_of_s = global_sets.find_root(&s);
+                debug_assert_eq!(set_of_s, sets.find_root(&s));
+                global_dead.set(set_of_s);  // set globally dead
+            }
+        }
+        return true;
+    }
 
-    /*// count non-trivial components:
-    let mut component_size: HashMap<usize, u32> = HashMap::new();
-    for s in network.states() {
-        if !global_sets.is_root(&s) {
-            let root = global_sets.find_root(&s);
-            let v = component_size.entry(root).or_insert(1);
-            *v += 1;
+    /// Push the next root which is not dead to the stack. Returns false if there is no such root.
+    fn start_search(&mut self) -> bool {
+        let state_count = self.network.state_count();
+        for root_seq in &mut self.roots {
+            let root = StateId { value: ((root_seq.value as u64 + self.key) % state_count) as u32 };
+            if self.sets.get_payload(&root) == DEAD { continue }
+            let set_of_root = self.global_sets.find_root(&root);
+            if self.global_dead.is_set(set_of_root) { continue }
+            self.explored += 1;
+            self.sets.set_payload(&root, 0);
+            self.stack.push((root, self.network.variables()));
+            return true;
         }
-        //println!("Root of {} is {}", s, sets.find_root(&s));
+        return false;
     }
-    println!("Non-trivial components: {}", component_size.len());*/
-}
 
+}
 
 /// This Disjoint sets structure does not store any payload or anything similar, so it can
 /// be fully implemented using atomics. Since we don't have to store a payload, we can identify
@@ -191,4 +242,78 @@ impl AtomicDisjointSets {
         }
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::*;
+
+    #[test]
+    fn parallel_scc_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            for parallelism in 1..5 {
+                parallel_scc_validated(network, parallelism);
+            }
+        }
+    }
+
+    /// Run [parallelism] workers of [parallel_scc] on one thread, interleaving their steps
+    /// according to a random schedule given by [seed].
+    fn interleaved_scc(network: &BooleanNetwork, parallelism: u32, seed: u64) -> SccDecomposition {
+        let global_sets = AtomicDisjointSets::new(network.state_count() as usize, 1234567890);
+        let global_dead = AtomicBitSet::new_empty(network.state_count() as usize);
+        let mut workers: Vec<SccWorker> = (0..parallelism)
+            .map(|id| SccWorker::new(network, &global_sets, &global_dead, id as u64, parallelism))
+            .collect();
+        let mut rnd = StdRng::seed_from_u64(seed);
+        while !workers.is_empty() {
+            let index = (rnd.next_u32() as usize) % workers.len();
+            if !workers[index].step() {
+                workers.remove(index);
+            }
+        }
+        return SccDecomposition::from_partition(network, |s| global_sets.find_root(s));
+    }
+
+    #[test]
+    fn parallel_scc_interleaved_workers() {
+        // Deterministic interleavings of several workers, where the global union-find structure
+        // contains unions which are not known to the local structure of a worker. Besides the
+        // partition, this checks the debug assertions on dead components in SccWorker::step.
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let expected = scc(network);
+            for parallelism in 2..5 {
+                for seed in 0..100 {
+                    assert!(expected == interleaved_scc(network, parallelism, seed));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_scc_interleaved_erb_b2_model() {
+        let network = erb_b2_model();
+        assert!(scc(&network) == interleaved_scc(&network, 4, 0));
+    }
+
+    #[test]
+    fn parallel_scc_erb_b2_model() {
+        parallel_scc_validated(&erb_b2_model(), 4);
+    }
+
+    // The following models have 2^26 states and take minutes in debug mode.
+
+    #[test]
+    #[ignore]
+    fn parallel_scc_t2dm_model() {
+        parallel_scc_validated(&t2dm_model(), 4);
+    }
+
+    #[test]
+    #[ignore]
+    fn parallel_scc_hspc_model() {
+        parallel_scc_validated(&hspc_model(), 4);
+    }
+
+}
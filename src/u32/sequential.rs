@@ -1,9 +1,10 @@
 use crate::bitset::BitSet;
 use rand::prelude::StdRng;
 use rand::{RngCore, SeedableRng};
-use crate::u32::bn::{StateId, BooleanNetwork, VariableIterator};
+use crate::u32::bn::{StateId, BooleanNetwork, VariableIterator, AsyncNetwork};
 use std::cmp::min;
-use std::collections::HashMap;
+use crate::u32::decomposition::{SccDecomposition, ReachableSccDecomposition, ComponentId};
+use crate::hash_sets::HashDisjointSets;
 
 /// Categorises states of the boolean network into disjoint sets of elements using the standard
 /// union-find structure. Additionally, for every set, we can remember one extra u32 value.
@@ -17,7 +18,79 @@ pub struct DisjointSets {
 pub const FRESH: u32 = std::u32::MAX;
 pub const DEAD: u32 = std::u32::MAX - 1;
 
-pub fn scc_alt(network: &BooleanNetwork) {
+/// Attractor is a terminal (bottom) SCC of the state space, i.e. a component which
+/// has no transitions leading outside of it. The type of states is generic, so that
+/// attractors of networks with wider states (see [crate::u64]) share this type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Attractor<S = StateId> {
+    /// A single state without any successors.
+    FixedPoint(S),
+    /// A terminal component with more than one state. States are sorted in increasing order.
+    Cyclic(Vec<S>)
+}
+
+impl <S> Attractor<S> where S: Copy {
+
+    /// Make an attractor from the sorted [states] of a terminal component.
+    fn from_states(mut states: Vec<S>) -> Attractor<S> {
+        return if states.len() == 1 {
+            Attractor::FixedPoint(states.pop().unwrap())
+        } else {
+            Attractor::Cyclic(states)
+        }
+    }
+
+    pub fn is_fixed_point(&self) -> bool {
+        return match self {
+            Attractor::FixedPoint(_) => true,
+            Attractor::Cyclic(_) => false,
+        }
+    }
+
+    /// States of this attractor in increasing order.
+    pub fn states(&self) -> Vec<S> {
+        return match self {
+            Attractor::FixedPoint(state) => vec![*state],
+            Attractor::Cyclic(states) => states.clone(),
+        }
+    }
+
+}
+
+/// Compute all attractors of the [network] using the sequential SCC decomposition.
+pub fn attractors(network: &BooleanNetwork) -> Vec<Attractor> {
+    return find_attractors(network, &scc(network));
+}
+
+/// Identify attractors in an already computed [decomposition] of the [network] state space.
+/// Attractors are returned in the order of their component IDs.
+pub fn find_attractors(network: &BooleanNetwork, decomposition: &SccDecomposition) -> Vec<Attractor> {
+    // A component is not terminal once we find a single transition leaving it.
+    let mut has_exit = BitSet::new_empty(decomposition.component_count());
+    for s in network.states() {
+        let component = decomposition.component_of(&s);
+        if has_exit.is_set(component.value as usize) { continue }
+        for var in network.variables() {
+            if let Some(t) = network.successor(&s, &var) {
+                if decomposition.component_of(&t) != component {
+                    has_exit.set(component.value as usize);
+                    break;
+                }
+            }
+        }
+    }
+
+    return decomposition.components()
+        .filter(|c| !has_exit.is_set(c.value as usize))
+        .map(|c: ComponentId| Attractor::from_states(decomposition.states_of(&c).collect()))
+        .collect();
+}
+
+/// Compute the SCC decomposition of the asynchronous state space of the [network].
+///
+/// This variant remembers dead components in a separate bit set instead of
+/// using a special payload value.
+pub fn scc_alt(network: &BooleanNetwork) -> SccDecomposition {
     let mut sets = DisjointSets::new(network.state_count() as usize, 1234567890);
     let mut dead = BitSet::new_empty(network.state_count() as usize);
     let mut on_stack = BitSet::new_empty(network.state_count() as usize);
@@ -67,20 +140,11 @@ pub fn scc_alt(network: &BooleanNetwork) {
 
     print!("\r");
 
-    // count non-trivial components:
-    let mut component_size: HashMap<usize, u32> = HashMap::new();
-    for s in network.states() {
-        if !sets.is_root(&s) {
-            let root = sets.find_root(&s);
-            let v = component_size.entry(root).or_insert(1);
-            *v += 1;
-        }
-        //println!("Root of {} is {}", s, sets.find_root(&s));
-    }
-    println!("Non-trivial components: {}", component_size.len());
+    return SccDecomposition::from_partition(network, |s| sets.find_root(s));
 }
 
-pub fn scc(network: &BooleanNetwork) {
+/// Compute the SCC decomposition of the asynchronous state space of the [network].
+pub fn scc(network: &BooleanNetwork) -> SccDecomposition {
     let mut sets = DisjointSets::new(network.state_count() as usize, 1234567890);
     let mut stack: Vec<(StateId, VariableIterator)> = Vec::new();
 
@@ -139,17 +203,79 @@ pub fn scc(network: &BooleanNetwork) {
     }
     print!("\r");
 
-    // count non-trivial components:
-    let mut component_size: HashMap<usize, u32> = HashMap::new();
-    for s in network.states() {
-        if !sets.is_root(&s) {
-            let root = sets.find_root(&s);
-            let v = component_size.entry(root).or_insert(1);
-            *v += 1;
+    return SccDecomposition::from_partition(network, |s| sets.find_root(s));
+}
+
+/// Compute the SCC decomposition of the part of the [network] state space which is reachable
+/// from the given [initial_states]. Unlike [scc], visited states are stored in a hash-based
+/// union-find structure, hence unreachable states are never touched and no memory is
+/// allocated for them. This also works for networks with wider states (see [crate::u64]).
+pub fn scc_reachable<N>(network: &N, initial_states: &[N::State]) -> ReachableSccDecomposition<N::State>
+    where N: AsyncNetwork
+{
+    let mut sets: HashDisjointSets<N::State> = HashDisjointSets::new(FRESH, 1234567890);
+    let mut stack: Vec<(N::State, VariableIterator)> = Vec::new();
+
+    for root in initial_states {
+        // once the exploration from a root finishes, all visited states are dead
+        if sets.get_payload(*root) != FRESH { continue }
+
+        sets.set_payload(*root, 0);
+        stack.push((*root, network.variables()));
+
+        while let Some((s, it)) = stack.last_mut() {
+            if let Some(var) = it.next() {
+                if let Some(t) = network.successor(s, &var) {
+                    let payload = sets.get_payload(t);
+                    if payload == FRESH {
+                        // t is newly discovered - add it to the stack!
+                        sets.set_payload(t, stack.len() as u32);
+                        stack.push((t, network.variables()));
+                        if stack.len() as u32 == DEAD { panic!("Stack overflow!") }
+                    } else if payload != DEAD {
+                        // t is already visited, but not dead, meaning we found a cycle.
+                        // Merge everything on the stack with t, skipping the already merged parts.
+                        let mut to_merge_index = stack.len() - 1;
+                        while sets.find_root(stack[to_merge_index].0) != sets.find_root(t) {
+                            to_merge_index = sets.get_payload(stack[to_merge_index].0) as usize;
+                            sets.union(stack[to_merge_index].0, t);
+                            to_merge_index -= 1;    // "virtual" pop
+                        }
+                    }
+                }
+            } else {
+                // State is fully explored and can be removed from the stack
+                let (s, _) = stack.pop().unwrap();
+                if sets.get_payload(s) as usize == stack.len() {
+                    // found component!
+                    sets.set_payload(s, DEAD)
+                }
+            }
         }
-        //println!("Root of {} is {}", s, sets.find_root(&s));
+        stack.clear();
     }
-    println!("Non-trivial components: {}", component_size.len());
+
+    let states: Vec<N::State> = sets.keys().collect();
+    return ReachableSccDecomposition::from_partition(states, |s| sets.find_root(s));
+}
+
+/// Identify attractors in an already computed reachable [decomposition]. A component is only
+/// considered terminal if all its successors are covered by the decomposition.
+/// Attractors are returned in the order of their component IDs.
+pub fn find_reachable_attractors<N>(network: &N, decomposition: &ReachableSccDecomposition<N::State>) -> Vec<Attractor<N::State>>
+    where N: AsyncNetwork
+{
+    return decomposition.components()
+        .filter(|c| {
+            decomposition.states_of(c).all(|s| {
+                network.variables().all(|var| match network.successor(&s, &var) {
+                    None => true,
+                    Some(t) => decomposition.component_of(&t) == Some(*c)
+                })
+            })
+        })
+        .map(|c| Attractor::from_states(decomposition.states_of(&c).collect()))
+        .collect();
 }
 
 impl DisjointSets {
@@ -221,4 +347,137 @@ impl DisjointSets {
         }
     }
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::*;
+
+    // Naive reference: two states are in the same component iff they reach each other.
+    fn reachability(network: &BooleanNetwork) -> Vec<Vec<bool>> {
+        let n = network.state_count() as usize;
+        let mut reach = vec![vec![false; n]; n];
+        for s in network.states() {
+            let mut stack = vec![s];
+            reach[s.value as usize][s.value as usize] = true;
+            while let Some(x) = stack.pop() {
+                for v in network.variables() {
+                    if let Some(t) = network.successor(&x, &v) {
+                        if !reach[s.value as usize][t.value as usize] {
+                            reach[s.value as usize][t.value as usize] = true;
+                            stack.push(t);
+                        }
+                    }
+                }
+            }
+        }
+        return reach;
+    }
+
+    fn assert_matches_reference(network: &BooleanNetwork, decomposition: &SccDecomposition) {
+        let reach = reachability(network);
+        for s in network.states() {
+            for t in network.states() {
+                let (i, j) = (s.value as usize, t.value as usize);
+                let same = decomposition.component_of(&s) == decomposition.component_of(&t);
+                assert_eq!(reach[i][j] && reach[j][i], same, "States {} and {}", network.format_state(&s), network.format_state(&t));
+            }
+        }
+        let total: u64 = decomposition.components().map(|c| decomposition.component_size(&c)).sum();
+        assert_eq!(network.state_count(), total);
+        for c in decomposition.components() {
+            assert_eq!(decomposition.component_size(&c), decomposition.states_of(&c).count() as u64);
+            assert!(decomposition.states_of(&c).all(|s| decomposition.component_of(&s) == c));
+        }
+    }
+
+    #[test]
+    fn scc_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            assert_matches_reference(network, &scc(network));
+            assert_matches_reference(network, &scc_alt(network));
+        }
+    }
+
+    #[test]
+    fn attractors_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let attractors = attractors(network);
+            assert!(!attractors.is_empty());
+            for attractor in &attractors {
+                let states = attractor.states();
+                assert_eq!(attractor.is_fixed_point(), states.len() == 1);
+                // every successor of an attractor state stays in the attractor
+                for s in &states {
+                    for v in network.variables() {
+                        if let Some(t) = network.successor(s, &v) {
+                            assert!(states.contains(&t));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn attractors_bistable() {
+        let network = demo_model_bistable();
+        let expected = vec![
+            Attractor::FixedPoint(StateId { value: 0b0111 }),
+            Attractor::FixedPoint(StateId { value: 0b1010 })
+        ];
+        assert_eq!(expected, attractors(&network));
+    }
+
+    #[test]
+    fn attractors_oscillation() {
+        let network = demo_model_oscillation();
+        assert!(attractors(&network).iter().any(|a| !a.is_fixed_point()));
+    }
+
+    #[test]
+    fn scc_reachable_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let full = scc(network);
+            let reach = reachability(network);
+            for initial in &[vec![StateId { value: 0 }], vec![StateId { value: 3 }, StateId { value: 12 }]] {
+                let reachable = scc_reachable(network, initial);
+                for s in network.states() {
+                    let is_reachable = initial.iter().any(|i| reach[i.value as usize][s.value as usize]);
+                    assert_eq!(is_reachable, reachable.contains(&s));
+                    if !is_reachable { continue }
+                    // reachable part is closed under successors, hence its components are full SCCs
+                    let c = reachable.component_of(&s).unwrap();
+                    assert_eq!(full.component_size(&full.component_of(&s)), reachable.component_size(&c));
+                    assert!(reachable.states_of(&c).all(|t| full.component_of(&t) == full.component_of(&s)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scc_reachable_from_all_states() {
+        let network = demo_model_oscillation();
+        let full = scc(&network);
+        let initial: Vec<StateId> = network.states().collect();
+        let reachable = scc_reachable(&network, &initial);
+        assert_eq!(network.state_count() as usize, reachable.state_count());
+        assert_eq!(full.non_trivial_count(), reachable.non_trivial_count());
+        for s in network.states() {
+            assert_eq!(Some(full.component_of(&s)), reachable.component_of(&s));
+        }
+    }
+
+    #[test]
+    fn scc_component_ids_are_ordered() {
+        let network = demo_model_oscillation();
+        let decomposition = scc(&network);
+        let mut last_min = None;
+        for c in decomposition.components() {
+            let min = decomposition.states_of(&c).next().unwrap();
+            assert!(last_min.map_or(true, |m: StateId| m.value < min.value));
+            last_min = Some(min);
+        }
+    }
+
+}
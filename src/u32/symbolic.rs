@@ -0,0 +1,423 @@
+//! Symbolic (BDD-based) representation of the asynchronous state space of a [BooleanNetwork].
+//!
+//! Sets of states are represented as BDDs over the unprimed variables of a worker created using
+//! [BDDWorker::new_primed]. Successors and predecessors of whole sets of states are computed
+//! without enumerating the 2^n states: since an asynchronous update of `v` only flips the value
+//! of `v`, the images are obtained by flipping `v` in the states where the update changes
+//! the state, which avoids the primed variables altogether. The transition relation of `v` over
+//! unprimed (current state) and primed (next state) variables is therefore only built when
+//! it is explicitly requested.
+//!
+//! Images are computed in a [BDDManager] owned by the graph, so the update conditions are
+//! imported only once and the intermediate results of all variables share one node table and
+//! operation caches (also across images of different sets).
+
+use crate::bdd::{BDD, BDDWorker, BDDManager, BDDHandle, SatCubes};
+use crate::bitset::BitSet;
+use crate::u32::bn::{BooleanNetwork, StateId, VariableId};
+use crate::u32::decomposition::SccDecomposition;
+use crate::u32::expression::BooleanExpression;
+use crate::u32::sequential::DisjointSets;
+use std::cell::{OnceCell, RefCell};
+
+/// The image manager is garbage collected once it has this many nodes (or twice the number
+/// of nodes which survived the last collection, if that is more).
+const IMAGE_GC_THRESHOLD: usize = 1 << 20;
+
+pub struct SymbolicAsyncGraph {
+    worker: BDDWorker,
+    var_count: u8,
+    // can_change[v] is the set of states where the update of v changes the state
+    can_change: Vec<BDD>,
+    // relations[v] is the transition relation of variable v, built on first use
+    relations: Vec<OnceCell<BDD>>,
+    unprimed_vars: Vec<u32>,
+    images: RefCell<ImageManager>
+}
+
+/// Manager used to compute images, together with the [SymbolicAsyncGraph::can_change]
+/// sets imported into it.
+struct ImageManager {
+    manager: BDDManager,
+    can_change: Vec<BDDHandle>,
+    gc_threshold: usize
+}
+
+impl SymbolicAsyncGraph {
+
+    /// Build the symbolic state space of the given [network].
+    pub fn new(network: &BooleanNetwork) -> SymbolicAsyncGraph {
+        let worker = BDDWorker::new_primed(network);
+        let variables: Vec<VariableId> = (0..(network.var_count() as usize)).map(VariableId::from_index).collect();
+        let unprimed_vars: Vec<u32> = variables.iter().map(BDDWorker::unprimed_var).collect();
+        // x_v != f_v(x), i.e. the value of v changes after an update
+        let can_change: Vec<BDD> = variables.iter().map(|v| {
+            let function = mk_expression_bdd(&worker, network.get_update_function(v));
+            worker.mk_xor(&worker.mk_var(BDDWorker::unprimed_var(v)), &function)
+        }).collect();
+        let mut manager = BDDManager::for_worker(&worker);
+        let imported: Vec<BDDHandle> = can_change.iter().map(|bdd| manager.import(bdd)).collect();
+        let images = ImageManager { manager, can_change: imported, gc_threshold: IMAGE_GC_THRESHOLD };
+        return SymbolicAsyncGraph {
+            worker,
+            var_count: network.var_count(),
+            can_change,
+            images: RefCell::new(images),
+            relations: variables.iter().map(|_| OnceCell::new()).collect(),
+            unprimed_vars
+        }
+    }
+
+    /// Worker which manages BDDs of this graph.
+    pub fn worker(&self) -> &BDDWorker {
+        return &self.worker;
+    }
+
+    /// Number of variables of the underlying network.
+    pub fn var_count(&self) -> u8 {
+        return self.var_count;
+    }
+
+    /// Transition relation of the given network [variable]: a BDD over unprimed and primed
+    /// variables which is satisfied by pairs of states connected by an update of [variable].
+    ///
+    /// The relation is a conjunction over all variables of the network, hence it is built
+    /// on the first call and then kept for later calls. Images computed by this graph
+    /// do not need it.
+    pub fn transition_relation(&self, variable: &VariableId) -> &BDD {
+        return self.relations[variable.index()].get_or_init(|| {
+            // v can change, its new value is the negation of the current one and nothing else changes
+            let worker = &self.worker;
+            let current = worker.mk_var(BDDWorker::unprimed_var(variable));
+            let next = worker.mk_var(BDDWorker::primed_var(variable));
+            let mut conditions: Vec<BDD> = vec![self.can_change[variable.index()].clone(), worker.mk_xor(&current, &next)];
+            for u in (0..(self.var_count as usize)).map(VariableId::from_index) {
+                if u != *variable {
+                    // x'_u <=> x_u
+                    let unchanged = worker.mk_equals(
+                        &worker.mk_var(BDDWorker::unprimed_var(&u)), &worker.mk_var(BDDWorker::primed_var(&u))
+                    );
+                    conditions.push(unchanged);
+                }
+            }
+            worker.mk_and_all(&conditions)
+        });
+    }
+
+    pub fn mk_empty(&self) -> BDD {
+        return self.worker.mk_false();
+    }
+
+    /// Set of all states of the network.
+    pub fn mk_unit(&self) -> BDD {
+        return self.worker.mk_true();
+    }
+
+    /// Set containing only the given [state].
+    pub fn mk_state(&self, state: &StateId) -> BDD {
+        let literals: Vec<BDD> = (0..(self.var_count as usize)).map(|v| {
+            let var = self.worker.mk_var(self.unprimed_vars[v]);
+            if (state.value >> v) & 1 == 1 { var } else { self.worker.mk_not(&var) }
+        }).collect();
+        return self.worker.mk_and_all(&literals);
+    }
+
+    /// Set of states which are present in the explicit [states] set, where index `i` of
+    /// the bit set corresponds to `StateId { value: i }`.
+    pub fn mk_from_bitset(&self, states: &BitSet) -> BDD {
+        // unprimed variables are sorted, and bit v of the state id is the value of variable v
+        return self.worker.mk_from_function(&self.unprimed_vars, |s| states.is_set(s as usize));
+    }
+
+    /// Set of states which are in the same component of [components] as the given [state].
+    ///
+    /// This evaluates [DisjointSets::find_root] for every one of the 2^n states (the sets are
+    /// mutable because `find_root` compresses paths), so converting all k components of
+    /// a decomposition this way takes `k * 2^n` lookups. To convert a whole decomposition,
+    /// use [SymbolicAsyncGraph::mk_from_decomposition] instead.
+    pub fn mk_from_component(&self, components: &mut DisjointSets, state: &StateId) -> BDD {
+        let root = components.find_root(state);
+        return self.worker.mk_from_function(&self.unprimed_vars, |s| {
+            components.find_root(&StateId { value: s as u32 }) == root
+        });
+    }
+
+    /// Sets of states of all components of the [decomposition], in the order of their IDs.
+    ///
+    /// Every state is visited once, since the decomposition already lists the states of
+    /// each component, so this takes `n * 2^n` steps in total regardless of the number of components.
+    pub fn mk_from_decomposition(&self, decomposition: &SccDecomposition) -> Vec<BDD> {
+        return decomposition.components().map(|c| {
+            let states: Vec<u64> = decomposition.states_of(&c).map(|s| s.value as u64).collect();
+            self.worker.mk_from_valuations(&self.unprimed_vars, &states)
+        }).collect();
+    }
+
+    /// Iterate over all states of the given [set]. States are not necessarily produced
+    /// in increasing order.
+    pub fn states<'a>(&'a self, set: &'a BDD) -> SymbolicStates<'a> {
+        return SymbolicStates { graph: self, cubes: self.worker.sat_cubes(set), fixed: 0, free: 0, next: None };
+    }
+
+    /// Explicit representation of the given [set], see also [SymbolicAsyncGraph::mk_from_bitset].
+    pub fn to_bitset(&self, set: &BDD) -> BitSet {
+        let mut result = BitSet::new_empty(1 << self.var_count);
+        for state in self.states(set) {
+            result.set(state.value as usize);
+        }
+        return result;
+    }
+
+    /// Pick one state of the given [set], or [None] if the set is empty.
+    pub fn pick_state(&self, set: &BDD) -> Option<StateId> {
+        return self.worker.sat_witness(set).map(|valuation| {
+            let mut value: u32 = 0;
+            for v in 0..(self.var_count as usize) {
+                if valuation[self.unprimed_vars[v] as usize] { value |= 1 << v }
+            }
+            StateId { value }
+        });
+    }
+
+    /// Number of states in the given [set].
+    pub fn state_count(&self, set: &BDD) -> u64 {
+        // primed variables are not used by sets of states, so every state is counted 2^n times
+        return (self.worker.exact_cardinality(set) >> self.var_count) as u64;
+    }
+
+    /// Set of states where the update of the given [variable] changes the state, i.e. states
+    /// which have a successor obtained by updating [variable].
+    pub fn var_can_change(&self, variable: &VariableId) -> &BDD {
+        return &self.can_change[variable.index()];
+    }
+
+    /// Successors of states in [set] obtained by updating the given [variable].
+    pub fn var_post(&self, variable: &VariableId, set: &BDD) -> BDD {
+        return self.image(set, &[variable.index()], true);
+    }
+
+    /// Predecessors of states in [set] obtained by updating the given [variable].
+    pub fn var_pre(&self, variable: &VariableId, set: &BDD) -> BDD {
+        return self.image(set, &[variable.index()], false);
+    }
+
+    /// All successors of states in [set].
+    pub fn post(&self, set: &BDD) -> BDD {
+        let variables: Vec<usize> = (0..(self.var_count as usize)).collect();
+        return self.image(set, &variables, true);
+    }
+
+    /// All predecessors of states in [set].
+    pub fn pre(&self, set: &BDD) -> BDD {
+        let variables: Vec<usize> = (0..(self.var_count as usize)).collect();
+        return self.image(set, &variables, false);
+    }
+
+    /// Union of successors (if [forward]) or predecessors of states in [set] obtained by
+    /// updating one of the given [variables]. The successors under `v` are the states of [set]
+    /// where `v` can change with `v` flipped, the predecessors are the states of the flipped
+    /// [set] where `v` can change.
+    fn image(&self, set: &BDD, variables: &[usize], forward: bool) -> BDD {
+        let mut images = self.images.borrow_mut();
+        let ImageManager { manager, can_change, gc_threshold } = &mut *images;
+        let set = manager.import(set);
+        let mut result = manager.mk_false();
+        for v in variables {
+            let var = self.unprimed_vars[*v];
+            let image = if forward {
+                let can_change = manager.mk_and(&set, &can_change[*v]);
+                manager.flip_var(&can_change, var)
+            } else {
+                let flipped = manager.flip_var(&set, var);
+                manager.mk_and(&flipped, &can_change[*v])
+            };
+            result = manager.mk_or(&result, &image);
+        }
+        let result = manager.export(&result);
+        if manager.node_count() > *gc_threshold {
+            *can_change = manager.gc(can_change);
+            *gc_threshold = std::cmp::max(IMAGE_GC_THRESHOLD, 2 * manager.node_count());
+        }
+        return result;
+    }
+
+}
+
+/// Iterator over states of a symbolic set, see [SymbolicAsyncGraph::states].
+pub struct SymbolicStates<'a> {
+    graph: &'a SymbolicAsyncGraph,
+    cubes: SatCubes<'a>,
+    // bits of state ids fixed by the current cube
+    fixed: u32,
+    // bits of state ids which are not constrained by the current cube
+    free: u32,
+    // next subset of free bits which has not been returned yet
+    next: Option<u32>
+}
+
+impl Iterator for SymbolicStates<'_> {
+    type Item = StateId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(subset) = self.next {
+                // enumerate subsets of free bits in increasing order
+                self.next = if subset == self.free { None } else {
+                    Some((subset | !self.free).wrapping_add(1) & self.free)
+                };
+                return Some(StateId { value: self.fixed | subset });
+            }
+            let cube = self.cubes.next()?;
+            self.fixed = 0;
+            self.free = 0;
+            for v in 0..(self.graph.var_count as usize) {
+                match cube[self.graph.unprimed_vars[v] as usize] {
+                    Some(true) => self.fixed |= 1 << v,
+                    Some(false) => (),
+                    None => self.free |= 1 << v
+                }
+            }
+            self.next = Some(0);
+        }
+    }
+}
+
+/// Translate the update function [expression] into a BDD over unprimed variables.
+fn mk_expression_bdd(worker: &BDDWorker, expression: &BooleanExpression) -> BDD {
+    return match expression {
+        BooleanExpression::Const(true) => worker.mk_true(),
+        BooleanExpression::Const(false) => worker.mk_false(),
+        BooleanExpression::Variable(v) => worker.mk_var(BDDWorker::unprimed_var(v)),
+        BooleanExpression::Not(e) => worker.mk_not(&mk_expression_bdd(worker, e)),
+        BooleanExpression::And(l, r) => worker.mk_and(&mk_expression_bdd(worker, l), &mk_expression_bdd(worker, r)),
+        BooleanExpression::Or(l, r) => worker.mk_or(&mk_expression_bdd(worker, l), &mk_expression_bdd(worker, r)),
+        BooleanExpression::Xor(l, r) => worker.mk_xor(&mk_expression_bdd(worker, l), &mk_expression_bdd(worker, r)),
+        BooleanExpression::Imp(l, r) => worker.mk_implies(&mk_expression_bdd(worker, l), &mk_expression_bdd(worker, r)),
+        BooleanExpression::Iff(l, r) => worker.mk_equals(&mk_expression_bdd(worker, l), &mk_expression_bdd(worker, r)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::*;
+    use crate::u32::sequential::scc;
+
+    #[test]
+    fn symbolic_post_and_pre_match_successors() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let graph = SymbolicAsyncGraph::new(network);
+            let worker = graph.worker();
+            let unprimed: Vec<u32> = network.variables().map(|v| BDDWorker::unprimed_var(&v)).collect();
+            for s in network.states() {
+                let state = graph.mk_state(&s);
+                let mut successors = graph.mk_empty();
+                for v in network.variables() {
+                    let expected = match network.successor(&s, &v) {
+                        Some(t) => graph.mk_state(&t),
+                        None => graph.mk_empty()
+                    };
+                    assert_eq!(expected, graph.var_post(&v, &state));
+                    // the same successors are obtained using the transition relation
+                    let next = worker.and_exists(&state, graph.transition_relation(&v), &unprimed);
+                    assert_eq!(expected, worker.swap_primed(&next));
+                    successors = worker.mk_or(&successors, &expected);
+                    // s is a predecessor of its successor
+                    if let Some(t) = network.successor(&s, &v) {
+                        let predecessors = graph.var_pre(&v, &graph.mk_state(&t));
+                        assert!(worker.is_false(&worker.mk_and(&state, &worker.mk_not(&predecessors))));
+                    }
+                }
+                assert_eq!(successors, graph.post(&state));
+            }
+        }
+    }
+
+    #[test]
+    fn symbolic_pre_of_all_states() {
+        let network = demo_model_bistable();
+        let graph = SymbolicAsyncGraph::new(&network);
+        // states with at least one successor
+        let expected: Vec<StateId> = network.states()
+            .filter(|s| network.variables().any(|v| network.successor(s, &v).is_some()))
+            .collect();
+        let pre = graph.pre(&graph.mk_unit());
+        assert_eq!(expected.len() as u64, graph.state_count(&pre));
+        for s in expected {
+            assert!(!graph.worker().is_false(&graph.worker().mk_and(&pre, &graph.mk_state(&s))));
+        }
+        assert_eq!(16, graph.state_count(&graph.mk_unit()));
+        assert_eq!(1, graph.state_count(&graph.mk_state(&StateId { value: 5 })));
+        assert_eq!(Some(StateId { value: 5 }), graph.pick_state(&graph.mk_state(&StateId { value: 5 })));
+        assert_eq!(None, graph.pick_state(&graph.mk_empty()));
+    }
+
+    #[test]
+    fn symbolic_bitset_conversion() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let graph = SymbolicAsyncGraph::new(network);
+            let count = network.state_count() as usize;
+            for modulus in 1..5 {
+                let expected: Vec<StateId> = network.states().filter(|s| s.value % modulus == 1 % modulus).collect();
+                let mut bitset = BitSet::new_empty(count);
+                for s in &expected { bitset.set(s.value as usize) }
+                let set = graph.mk_from_bitset(&bitset);
+                let states: Vec<BDD> = expected.iter().map(|s| graph.mk_state(s)).collect();
+                assert_eq!(graph.worker().mk_or_all(&states), set);
+                let mut actual: Vec<StateId> = graph.states(&set).collect();
+                actual.sort_by_key(|s| s.value);
+                assert_eq!(expected, actual);
+                let result = graph.to_bitset(&set);
+                for i in 0..count {
+                    assert_eq!(bitset.is_set(i), result.is_set(i));
+                }
+            }
+            assert_eq!(0, graph.states(&graph.mk_empty()).count());
+            assert_eq!(count, graph.states(&graph.mk_unit()).count());
+        }
+    }
+
+    #[test]
+    fn symbolic_component_conversion() {
+        let network = demo_model_bistable();
+        let graph = SymbolicAsyncGraph::new(&network);
+        let mut components = DisjointSets::new(network.state_count() as usize, 0);
+        // states with the same lowest two bits are in the same component
+        for s in network.states() {
+            components.union(s, StateId { value: s.value & 0b11 });
+        }
+        for r in 0..4 {
+            let component = graph.mk_from_component(&mut components, &StateId { value: r });
+            let expected: Vec<StateId> = network.states().filter(|s| s.value & 0b11 == r).collect();
+            let mut actual: Vec<StateId> = graph.states(&component).collect();
+            actual.sort_by_key(|s| s.value);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn symbolic_decomposition_conversion() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let graph = SymbolicAsyncGraph::new(network);
+            let decomposition = scc(network);
+            let components = graph.mk_from_decomposition(&decomposition);
+            assert_eq!(decomposition.component_count(), components.len());
+            for (c, set) in decomposition.components().zip(components.iter()) {
+                let mut actual: Vec<StateId> = graph.states(set).collect();
+                actual.sort_by_key(|s| s.value);
+                assert_eq!(decomposition.states_of(&c).collect::<Vec<StateId>>(), actual);
+            }
+        }
+    }
+
+    #[test]
+    fn symbolic_graph_large_model() {
+        let network = erb_b2_model();
+        let graph = SymbolicAsyncGraph::new(&network);
+        let s = StateId { value: 0 };
+        let post = graph.post(&graph.mk_state(&s));
+        let expected = network.variables().filter(|v| network.successor(&s, v).is_some()).count();
+        assert_eq!(expected as u64, graph.state_count(&post));
+    }
+
+}
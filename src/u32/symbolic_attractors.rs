@@ -0,0 +1,236 @@
+use crate::bdd::BDD;
+use crate::u32::bn::VariableId;
+use crate::u32::symbolic::SymbolicAsyncGraph;
+use crate::u32::symbolic_scc::{reach_backward, reach_forward};
+
+/// Trimming stops once a step removes less than `1/TRIM_RATIO` of the remaining states,
+/// since the cost of every step (one symbolic image of the whole universe) then no longer
+/// pays off.
+const TRIM_RATIO: u64 = 1000;
+
+/// Compute all attractors (terminal components) of the [graph] symbolically. Unlike
+/// [crate::u32::symbolic_scc::symbolic_scc], non-terminal components are never
+/// computed, which makes this much faster for networks with large state spaces.
+///
+/// The algorithm keeps a universe of states which can still contain attractors. Initially,
+/// the universe is trimmed and reduced using [interleaved_reduction]. Then, in every
+/// iteration, the universe is trimmed and a pivot is selected. If the forward reachable
+/// set of the pivot is also backward reachable from it (i.e. it is closed and strongly
+/// connected), it is an attractor and its whole basin is removed from the universe.
+/// Otherwise the pivot (and everything that can reach it) is not part of any attractor,
+/// and the next pivot is selected from the states reachable from it.
+pub fn symbolic_attractors(graph: &SymbolicAsyncGraph) -> Vec<BDD> {
+    let worker = graph.worker();
+    let unit = graph.mk_unit();
+    let has_successor = graph.pre(&unit);
+    let mut attractors: Vec<BDD> = Vec::new();
+    let mut universe = interleaved_reduction(graph, &trim(graph, &unit, &has_successor));
+    // states of the universe reachable from the last non-terminal pivot
+    let mut candidates = graph.mk_empty();
+    loop {
+        universe = trim(graph, &universe, &has_successor);
+        // Prefer pivots which are reachable from the previous pivot, since they are
+        // "closer" to some attractor.
+        candidates = worker.mk_and(&candidates, &universe);
+        let pivot = match graph.pick_state(&candidates).or_else(|| graph.pick_state(&universe)) {
+            Some(state) => graph.mk_state(&state),
+            None => return attractors
+        };
+        // The forward set is computed in the whole state space, since attractors can
+        // be reached through states which are no longer in the universe.
+        let forward = reach_forward(graph, &pivot, &unit);
+        let backward = reach_backward(graph, &pivot, &forward);
+        let is_terminal = worker.is_false(&worker.mk_and(&forward, &worker.mk_not(&backward)));
+        let to_remove = if is_terminal {
+            let basin = reach_backward(graph, &forward, &universe);
+            attractors.push(forward);
+            basin
+        } else {
+            candidates = worker.mk_and(&forward, &worker.mk_not(&backward));
+            reach_backward(graph, &pivot, &universe)
+        };
+        universe = worker.mk_and(&universe, &worker.mk_not(&to_remove));
+    }
+}
+
+/// Repeatedly remove states which have no predecessors in the [universe] but have some
+/// successors. Such states cannot be part of an attractor: fixed points have no successors
+/// and every state of a larger attractor has a predecessor in the same attractor.
+/// See [TRIM_RATIO] for when the trimming stops.
+fn trim(graph: &SymbolicAsyncGraph, universe: &BDD, has_successor: &BDD) -> BDD {
+    let worker = graph.worker();
+    let mut result = universe.clone();
+    loop {
+        let with_predecessor = graph.post(&result);
+        let sources = worker.mk_and(&worker.mk_and(&result, &worker.mk_not(&with_predecessor)), has_successor);
+        if worker.is_false(&sources) { return result }
+        let is_worth_continuing = graph.state_count(&sources) * TRIM_RATIO >= graph.state_count(&result);
+        result = worker.mk_and(&result, &worker.mk_not(&sources));
+        if !is_worth_continuing { return result }
+    }
+}
+
+/// Remove states which cannot be part of an attractor using the transitions of individual
+/// variables (transition guided reduction). Let `S` be the states of the [universe] where
+/// the update of a variable `v` changes the state and `F` the states reachable from the
+/// successors of `S` under `v`. Every attractor state of `S` is in `F` (it is reachable from
+/// its own successor), and `F` contains either all or none of the states of an attractor.
+/// Hence the states which can reach `S \ F`, as well as the states outside of `F` which
+/// can reach `F`, are not in any attractor and can be removed.
+///
+/// The reachability procedures of all variables are interleaved: in every step, the one with
+/// the smallest BDD performs one image, and the removed states immediately shrink
+/// the universe of all other procedures.
+fn interleaved_reduction(graph: &SymbolicAsyncGraph, universe: &BDD) -> BDD {
+    let worker = graph.worker();
+    let mut universe = universe.clone();
+    let mut processes: Vec<Reduction> = Vec::new();
+    for v in 0..(graph.var_count() as usize) {
+        let variable = VariableId::from_index(v);
+        let sources = worker.mk_and(graph.var_can_change(&variable), &universe);
+        if worker.is_false(&sources) { continue }
+        let successors = worker.mk_and(&graph.var_post(&variable, &sources), &universe);
+        processes.push(Reduction::Forward { sources, reach: successors });
+    }
+    while !processes.is_empty() {
+        let smallest = (0..processes.len()).min_by_key(|i| processes[*i].reach().size()).unwrap();
+        if !processes[smallest].step(graph, &universe) { continue }
+        match processes.swap_remove(smallest) {
+            Reduction::Forward { sources, reach } => {
+                let outside = worker.mk_and(&sources, &worker.mk_not(&reach));
+                processes.push(Reduction::Backward { reach: outside, keep: graph.mk_empty() });
+                processes.push(Reduction::Backward { reach: reach.clone(), keep: reach });
+            }
+            Reduction::Backward { reach, keep } => {
+                let to_remove = worker.mk_and(&reach, &worker.mk_not(&keep));
+                universe = worker.mk_and(&universe, &worker.mk_not(&to_remove));
+            }
+        }
+    }
+    return universe;
+}
+
+/// Reachability procedure of [interleaved_reduction].
+enum Reduction {
+    /// Forward closure of the successors of [sources] (under one variable).
+    Forward { sources: BDD, reach: BDD },
+    /// Backward closure of the initial [reach]. Once finished, states which
+    /// are not in [keep] are removed from the universe.
+    Backward { reach: BDD, keep: BDD }
+}
+
+impl Reduction {
+
+    fn reach(&self) -> &BDD {
+        return match self {
+            Reduction::Forward { reach, .. } => reach,
+            Reduction::Backward { reach, .. } => reach
+        }
+    }
+
+    /// Extend the reachable set by one image within [universe]. Returns true
+    /// when the set did not change, i.e. the procedure is finished.
+    fn step(&mut self, graph: &SymbolicAsyncGraph, universe: &BDD) -> bool {
+        let worker = graph.worker();
+        let (reach, image) = match self {
+            Reduction::Forward { reach, .. } => { let image = graph.post(reach); (reach, image) }
+            Reduction::Backward { reach, .. } => { let image = graph.pre(reach); (reach, image) }
+        };
+        let new = worker.mk_and(&worker.mk_and(&image, universe), &worker.mk_not(reach));
+        if worker.is_false(&new) { return true }
+        *reach = worker.mk_or(reach, &new);
+        return false;
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::bn::{BooleanNetwork, BooleanNetworkBuilder};
+    use crate::u32::expression::{BooleanExpression, var};
+    use crate::u32::models::*;
+    use crate::u32::sequential::attractors;
+    use rand::prelude::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    /// Network where every update function is a disjunction of two random clauses
+    /// with two random literals each.
+    fn mk_random_network(rnd: &mut StdRng, var_count: usize) -> BooleanNetwork {
+        let mut builder = BooleanNetworkBuilder::new();
+        let variables: Vec<_> = (0..var_count).map(|i| builder.make_variable(&format!("x{}", i))).collect();
+        for variable in &variables {
+            let mut literal = || {
+                let literal = var(variables[rnd.next_u32() as usize % var_count]);
+                if rnd.next_u32() & 1 == 0 { literal } else { !literal }
+            };
+            let function: BooleanExpression = (literal() & literal()) | (literal() & literal());
+            builder.update_function(variable, function);
+        }
+        return builder.build_network();
+    }
+
+    /// Check that the symbolic attractors are the same as the explicit ones.
+    fn check_attractors(network: &BooleanNetwork) {
+        let graph = SymbolicAsyncGraph::new(network);
+        let worker = graph.worker();
+        let expected = attractors(network);
+        let actual = symbolic_attractors(&graph);
+        assert_eq!(expected.len(), actual.len());
+        for attractor in actual {
+            let state = graph.pick_state(&attractor).unwrap();
+            let explicit = expected.iter().find(|a| a.states().contains(&state))
+                .expect("Symbolic attractor is not an explicit attractor.");
+            let states: Vec<BDD> = explicit.states().iter().map(|s| graph.mk_state(s)).collect();
+            assert_eq!(worker.mk_or_all(&states), attractor);
+        }
+    }
+
+    #[test]
+    fn symbolic_attractors_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            check_attractors(network);
+        }
+    }
+
+    #[test]
+    fn symbolic_attractors_random_networks() {
+        let mut rnd = StdRng::seed_from_u64(1234567890);
+        for _ in 0..10 {
+            check_attractors(&mk_random_network(&mut rnd, 10));
+        }
+    }
+
+    #[test]
+    fn interleaved_reduction_keeps_attractors() {
+        let mut rnd = StdRng::seed_from_u64(987654321);
+        for _ in 0..10 {
+            let network = mk_random_network(&mut rnd, 10);
+            let graph = SymbolicAsyncGraph::new(&network);
+            let reduced = interleaved_reduction(&graph, &graph.mk_unit());
+            for attractor in attractors(&network) {
+                for state in attractor.states() {
+                    assert!(graph.to_bitset(&reduced).is_set(state.value as usize));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symbolic_attractors_erb_b2() {
+        check_attractors(&erb_b2_model());
+    }
+
+    #[test]
+    #[ignore]
+    fn symbolic_attractors_t2dm() {
+        check_attractors(&t2dm_model());
+    }
+
+    #[test]
+    #[ignore]
+    fn symbolic_attractors_hspc() {
+        check_attractors(&hspc_model());
+    }
+
+}
@@ -0,0 +1,254 @@
+use crate::bdd::BDD;
+use crate::u32::symbolic::SymbolicAsyncGraph;
+
+/// Strategy used by [symbolic_scc] to find the component of a pivot state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SccStrategy {
+    /// Compute full forward and backward reachable sets of the pivot (Xie-Beerel).
+    ForwardBackward,
+    /// Compute forward and backward sets in lockstep and stop once one of them converges
+    /// (Bloem-Gabow-Somenzi). This avoids computing a large reachable set when the other
+    /// one is small.
+    Lockstep
+}
+
+/// Compute the SCC decomposition of the whole state space of the [graph] symbolically.
+/// Every component (including the trivial ones) is returned as a set of states.
+///
+/// The algorithm maintains a stack of state sets which are closed under SCCs. Every set is
+/// first trimmed, i.e. states without predecessors or successors in the set are removed
+/// as trivial components, then a pivot state is selected and its component is computed
+/// as the intersection of the forward and backward reachable sets. Remaining states are then
+/// split into sets which again contain only whole components.
+pub fn symbolic_scc(graph: &SymbolicAsyncGraph, strategy: SccStrategy) -> Vec<BDD> {
+    let (mut components, trimmed) = decompose(graph, strategy);
+    components.extend(graph.states(&trimmed).map(|state| graph.mk_state(&state)));
+    return components;
+}
+
+/// Same as [symbolic_scc], but only components with more than one state are returned. The
+/// trivial components removed by trimming are never enumerated, which makes this much faster
+/// for networks where most states are not on a cycle.
+pub fn symbolic_non_trivial_scc(graph: &SymbolicAsyncGraph, strategy: SccStrategy) -> Vec<BDD> {
+    let (components, _) = decompose(graph, strategy);
+    return components.into_iter().filter(|component| graph.state_count(component) > 1).collect();
+}
+
+/// States which have to be trimmed from a set of states, see [trim].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Trim {
+    /// States without predecessors in the set.
+    Sources,
+    /// States without successors in the set.
+    Sinks,
+    /// States without predecessors or without successors in the set.
+    Both
+}
+
+/// Decompose the state space into components found using pivots and the set of states
+/// removed by trimming (each of them is a trivial component).
+fn decompose(graph: &SymbolicAsyncGraph, strategy: SccStrategy) -> (Vec<BDD>, BDD) {
+    let worker = graph.worker();
+    let mut components: Vec<BDD> = Vec::new();
+    let mut trimmed = graph.mk_empty();
+    let mut stack: Vec<(BDD, Trim)> = vec![(graph.mk_unit(), Trim::Both)];
+    while let Some((universe, to_trim)) = stack.pop() {
+        let universe = trim(graph, &universe, to_trim, &mut trimmed);
+        let pivot = match graph.pick_state(&universe) {
+            Some(state) => graph.mk_state(&state),
+            None => continue
+        };
+        let (component, to_process) = match strategy {
+            SccStrategy::ForwardBackward => forward_backward(graph, &universe, &pivot),
+            SccStrategy::Lockstep => lockstep(graph, &universe, &pivot)
+        };
+        for (set, to_trim) in to_process {
+            if !worker.is_false(&set) { stack.push((set, to_trim)) }
+        }
+        components.push(component);
+    }
+    return (components, trimmed);
+}
+
+/// Repeatedly remove states which have no predecessors or no successors (as given by [to_trim])
+/// within the [universe] and add them to [trimmed]. Such states are not on any cycle, so each
+/// of them is a trivial component, and the rest of the [universe] is still closed under components.
+///
+/// Once a set is trimmed in both directions, splitting it using a forward closed set only
+/// creates new sinks in the part outside of the closed set and new sources inside it
+/// (and vice versa for backward closed sets). Hence only one direction has to be checked.
+fn trim(graph: &SymbolicAsyncGraph, universe: &BDD, to_trim: Trim, trimmed: &mut BDD) -> BDD {
+    let worker = graph.worker();
+    let mut result = universe.clone();
+    loop {
+        let to_keep = match to_trim {
+            Trim::Sources => graph.post(&result),
+            Trim::Sinks => graph.pre(&result),
+            Trim::Both => worker.mk_and(&graph.post(&result), &graph.pre(&result))
+        };
+        let to_remove = worker.mk_and(&result, &worker.mk_not(&to_keep));
+        if worker.is_false(&to_remove) { return result }
+        *trimmed = worker.mk_or(trimmed, &to_remove);
+        result = worker.mk_and(&result, &to_keep);
+    }
+}
+
+/// Compute all states of [universe] reachable from [initial] using transitions within [universe].
+pub fn reach_forward(graph: &SymbolicAsyncGraph, initial: &BDD, universe: &BDD) -> BDD {
+    let worker = graph.worker();
+    let mut result = initial.clone();
+    loop {
+        let successors = worker.mk_and(&graph.post(&result), universe);
+        let new = worker.mk_and(&successors, &worker.mk_not(&result));
+        if worker.is_false(&new) { return result }
+        result = worker.mk_or(&result, &new);
+    }
+}
+
+/// Compute all states of [universe] which can reach [initial] using transitions within [universe].
+pub fn reach_backward(graph: &SymbolicAsyncGraph, initial: &BDD, universe: &BDD) -> BDD {
+    let worker = graph.worker();
+    let mut result = initial.clone();
+    loop {
+        let predecessors = worker.mk_and(&graph.pre(&result), universe);
+        let new = worker.mk_and(&predecessors, &worker.mk_not(&result));
+        if worker.is_false(&new) { return result }
+        result = worker.mk_or(&result, &new);
+    }
+}
+
+/// Compute the component of [pivot] together with the sets which remain to be decomposed.
+fn forward_backward(graph: &SymbolicAsyncGraph, universe: &BDD, pivot: &BDD) -> (BDD, Vec<(BDD, Trim)>) {
+    let worker = graph.worker();
+    let forward = reach_forward(graph, pivot, universe);
+    let backward = reach_backward(graph, pivot, &forward);
+    let component = worker.mk_and(&forward, &backward);
+    let remaining = worker.mk_and(universe, &worker.mk_not(&forward));
+    let forward_rest = worker.mk_and(&forward, &worker.mk_not(&component));
+    return (component, vec![(remaining, Trim::Sinks), (forward_rest, Trim::Sources)]);
+}
+
+/// Same as [forward_backward], but the forward and backward sets are extended one step at
+/// a time until one of them converges. The other set is then only extended while it keeps
+/// discovering states of the converged set, since these are exactly the states of the component.
+fn lockstep(graph: &SymbolicAsyncGraph, universe: &BDD, pivot: &BDD) -> (BDD, Vec<(BDD, Trim)>) {
+    let worker = graph.worker();
+    let (mut forward, mut backward) = (pivot.clone(), pivot.clone());
+    let (mut forward_frontier, mut backward_frontier) = (pivot.clone(), pivot.clone());
+    while !worker.is_false(&forward_frontier) && !worker.is_false(&backward_frontier) {
+        let successors = worker.mk_and(&graph.post(&forward_frontier), universe);
+        forward_frontier = worker.mk_and(&successors, &worker.mk_not(&forward));
+        forward = worker.mk_or(&forward, &forward_frontier);
+        let predecessors = worker.mk_and(&graph.pre(&backward_frontier), universe);
+        backward_frontier = worker.mk_and(&predecessors, &worker.mk_not(&backward));
+        backward = worker.mk_or(&backward, &backward_frontier);
+    }
+    let (component, converged, is_forward) = if worker.is_false(&forward_frontier) {
+        while !worker.is_false(&worker.mk_and(&backward_frontier, &forward)) {
+            let predecessors = worker.mk_and(&graph.pre(&backward_frontier), universe);
+            backward_frontier = worker.mk_and(&predecessors, &worker.mk_not(&backward));
+            backward = worker.mk_or(&backward, &backward_frontier);
+        }
+        (worker.mk_and(&forward, &backward), forward, true)
+    } else {
+        while !worker.is_false(&worker.mk_and(&forward_frontier, &backward)) {
+            let successors = worker.mk_and(&graph.post(&forward_frontier), universe);
+            forward_frontier = worker.mk_and(&successors, &worker.mk_not(&forward));
+            forward = worker.mk_or(&forward, &forward_frontier);
+        }
+        (worker.mk_and(&forward, &backward), backward, false)
+    };
+    let remaining = worker.mk_and(universe, &worker.mk_not(&converged));
+    let converged_rest = worker.mk_and(&converged, &worker.mk_not(&component));
+    let (remaining_trim, rest_trim) = if is_forward { (Trim::Sinks, Trim::Sources) } else { (Trim::Sources, Trim::Sinks) };
+    return (component, vec![(remaining, remaining_trim), (converged_rest, rest_trim)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::bn::BooleanNetwork;
+    use crate::u32::models::*;
+    use crate::u32::decomposition::SccDecomposition;
+    use crate::u32::sequential::scc;
+
+    /// Check that the symbolic components form the same partition as the explicit decomposition.
+    fn check_scc(network: &BooleanNetwork, strategy: SccStrategy) {
+        let graph = SymbolicAsyncGraph::new(network);
+        let worker = graph.worker();
+        let expected = scc(network);
+        let components = symbolic_scc(&graph, strategy);
+        assert_eq!(expected.component_count(), components.len());
+        for component in components {
+            let state = graph.pick_state(&component).unwrap();
+            let c = expected.component_of(&state);
+            assert_eq!(expected.component_size(&c), graph.state_count(&component));
+            let states: Vec<BDD> = expected.states_of(&c).map(|s| graph.mk_state(&s)).collect();
+            assert_eq!(worker.mk_or_all(&states), component);
+        }
+    }
+
+    /// Check that the non-trivial symbolic components are exactly the non-trivial components
+    /// of the explicit decomposition [expected].
+    fn check_non_trivial_scc(network: &BooleanNetwork, expected: &SccDecomposition, strategy: SccStrategy) {
+        let graph = SymbolicAsyncGraph::new(network);
+        let components = symbolic_non_trivial_scc(&graph, strategy);
+        assert_eq!(expected.non_trivial_count(), components.len());
+        for component in components {
+            let c = expected.component_of(&graph.pick_state(&component).unwrap());
+            assert_eq!(expected.component_size(&c), graph.state_count(&component));
+            assert!(graph.states(&component).all(|s| expected.component_of(&s) == c));
+        }
+    }
+
+    #[test]
+    fn symbolic_scc_demo_models() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            check_scc(network, SccStrategy::ForwardBackward);
+            check_scc(network, SccStrategy::Lockstep);
+        }
+    }
+
+    #[test]
+    fn symbolic_reachability() {
+        let network = demo_model_bistable();
+        let graph = SymbolicAsyncGraph::new(&network);
+        let worker = graph.worker();
+        let unit = graph.mk_unit();
+        for s in network.states() {
+            let state = graph.mk_state(&s);
+            let forward = reach_forward(&graph, &state, &unit);
+            let backward = reach_backward(&graph, &state, &unit);
+            for t in network.states() {
+                let target = graph.mk_state(&t);
+                // t is reachable from s exactly when s can reach t backwards
+                let is_forward = !worker.is_false(&worker.mk_and(&forward, &target));
+                let is_backward = !worker.is_false(&worker.mk_and(&reach_backward(&graph, &target, &unit), &state));
+                assert_eq!(is_forward, is_backward);
+            }
+            assert!(!worker.is_false(&worker.mk_and(&backward, &state)));
+        }
+    }
+
+    #[test]
+    fn symbolic_scc_demo_models_non_trivial() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let expected = scc(network);
+            check_non_trivial_scc(network, &expected, SccStrategy::ForwardBackward);
+            check_non_trivial_scc(network, &expected, SccStrategy::Lockstep);
+        }
+    }
+
+    // The real models have millions of trivial components, hence only the non-trivial
+    // ones are compared (these are found without enumerating the trivial ones). On the
+    // larger models (t2dm, hspc), the symbolic search is still too slow for a unit test.
+
+    #[test]
+    fn symbolic_scc_erb_b2() {
+        let network = erb_b2_model();
+        let expected = scc(&network);
+        check_non_trivial_scc(&network, &expected, SccStrategy::ForwardBackward);
+        check_non_trivial_scc(&network, &expected, SccStrategy::Lockstep);
+    }
+
+}
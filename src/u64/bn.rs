@@ -0,0 +1,193 @@
+use std::ops::{BitOr, BitXor};
+use std::fmt::{Display, Formatter, Error};
+use crate::u32::expression::{BooleanExpression, CompiledExpression};
+
+pub use crate::u32::bn::{VariableId, VariableIterator, AsyncNetwork};
+
+/// Every state ID is internally stored as u64 and represents a binary encoding
+/// of the boolean vector of network variables. The encoding is the same as in
+/// [crate::u32::bn::StateId], i.e. variable with ID 0 is stored in the least
+/// significant bit of the state integer.
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone, Ord, PartialOrd)]
+pub struct StateId { pub value: u64 }
+
+/// Boolean network with up to 64 variables. Apart from the width of states, it behaves
+/// exactly like [crate::u32::bn::BooleanNetwork], however, it does not provide an iterator
+/// over all states since there are typically too many of them.
+pub struct BooleanNetwork {
+    variable_names: Vec<String>,
+    update_functions: Vec<BooleanExpression>,
+    compiled_functions: Vec<CompiledExpression>
+}
+
+/// Boolean network builder for networks with up to 64 variables.
+/// See [crate::u32::bn::BooleanNetworkBuilder] for details.
+pub struct BooleanNetworkBuilder {
+    builder: crate::u32::bn::BooleanNetworkBuilder
+}
+
+impl BitOr<VariableId> for StateId {
+    type Output = bool;
+
+    fn bitor(self, rhs: VariableId) -> Self::Output {
+        return (self.value >> rhs.index()) & 1 == 1
+    }
+
+}
+
+impl BitXor<VariableId> for StateId {
+    type Output = StateId;
+
+    fn bitxor(self, rhs: VariableId) -> Self::Output {
+        return StateId { value: self.value ^ (1_u64 << rhs.index()) }
+    }
+
+}
+
+impl Display for StateId {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_fmt(format_args!("State({})", self.value))
+    }
+}
+
+impl BooleanNetwork {
+
+    fn new(variable_names: Vec<String>, update_functions: Vec<BooleanExpression>) -> BooleanNetwork {
+        return BooleanNetwork {
+            variable_names,
+            compiled_functions: update_functions.iter().map(CompiledExpression::compile).collect(),
+            update_functions
+        }
+    }
+
+    /// The name of the given [variable].
+    pub fn get_variable_name(&self, variable: &VariableId) -> &String {
+        return &self.variable_names[variable.index()];
+    }
+
+    /// The update function of the given [variable].
+    pub fn get_update_function(&self, variable: &VariableId) -> &BooleanExpression {
+        return &self.update_functions[variable.index()];
+    }
+
+    pub fn var_count(&self) -> u8 {
+        return self.update_functions.len() as u8;
+    }
+
+    pub fn variables(&self) -> VariableIterator {
+        return VariableIterator::new(self.var_count());
+    }
+
+    /// Check if [state] has a successor in dimension given by [variable]. If yes,
+    /// return such successor, otherwise return [None].
+    pub fn successor(&self, state: &StateId, variable: &VariableId) -> Option<StateId> {
+        let index = variable.index();
+        let target = self.compiled_functions[index].evaluate(&self.update_functions[index], |v| *state | v);
+        return if *state | *variable == target { None } else { Some(*state ^ *variable) }
+    }
+
+}
+
+impl AsyncNetwork for BooleanNetwork {
+    type State = StateId;
+
+    fn var_count(&self) -> u8 {
+        return BooleanNetwork::var_count(self);
+    }
+
+    fn get_variable_name(&self, variable: &VariableId) -> &String {
+        return BooleanNetwork::get_variable_name(self, variable);
+    }
+
+    fn successor(&self, state: &StateId, variable: &VariableId) -> Option<StateId> {
+        return BooleanNetwork::successor(self, state, variable);
+    }
+
+}
+
+impl From<StateId> for u64 {
+    fn from(state: StateId) -> Self {
+        return state.value;
+    }
+}
+
+/// Every 32-bit network is also a valid 64-bit network.
+impl From<&crate::u32::bn::BooleanNetwork> for BooleanNetwork {
+    fn from(network: &crate::u32::bn::BooleanNetwork) -> Self {
+        let mut variables: Vec<VariableId> = network.variables().collect();
+        variables.sort();
+        return BooleanNetwork::new(
+            variables.iter().map(|v| network.get_variable_name(v).clone()).collect(),
+            variables.iter().map(|v| network.get_update_function(v).clone()).collect()
+        );
+    }
+}
+
+impl BooleanNetworkBuilder {
+
+    /// Make a new empty boolean network builder.
+    pub fn new() -> BooleanNetworkBuilder {
+        return BooleanNetworkBuilder {
+            builder: crate::u32::bn::BooleanNetworkBuilder::with_variable_limit(64)
+        }
+    }
+
+    pub(crate) fn from_builder(builder: crate::u32::bn::BooleanNetworkBuilder) -> BooleanNetworkBuilder {
+        return BooleanNetworkBuilder { builder }
+    }
+
+    /// Create a new variable in this network.
+    /// Panics if the variable already exists or the network is too large.
+    pub fn make_variable(&mut self, name: &str) -> VariableId {
+        return self.builder.make_variable(name);
+    }
+
+    /// Associate an update function with a variable.
+    /// Panics if the variable (or any variable used in the function) does not exist or if
+    /// it already has a function defined.
+    pub fn update_function(&mut self, variable: &VariableId, function: BooleanExpression) {
+        self.builder.update_function(variable, function);
+    }
+
+    /// Consume this builder into a full-fledged boolean network.
+    pub fn build_network(self) -> BooleanNetwork {
+        let (variable_names, update_functions) = self.builder.into_parts();
+        return BooleanNetwork::new(variable_names, update_functions);
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::expression::var;
+
+    #[test]
+    fn network_with_64_variables() {
+        let mut builder = BooleanNetworkBuilder::new();
+        let vars: Vec<VariableId> = (0..64).map(|i| builder.make_variable(&format!("v{}", i))).collect();
+        // every variable copies its predecessor, the first one is negated
+        builder.update_function(&vars[0], !var(vars[63]));
+        for i in 1..64 {
+            builder.update_function(&vars[i], var(vars[i - 1]));
+        }
+        let network = builder.build_network();
+        assert_eq!(64, network.var_count());
+        assert_eq!(Some(vars[63]), network.find_variable("v63"));
+        let state = StateId { value: 0 };
+        assert_eq!(Some(StateId { value: 1 }), network.successor(&state, &vars[0]));
+        assert_eq!(None, network.successor(&state, &vars[1]));
+        let state = StateId { value: 1 << 62 };
+        assert_eq!(Some(StateId { value: 3 << 62 }), network.successor(&state, &vars[63]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn network_with_65_variables() {
+        let mut builder = BooleanNetworkBuilder::new();
+        for i in 0..65 {
+            builder.make_variable(&format!("v{}", i));
+        }
+    }
+
+}
@@ -0,0 +1,30 @@
+//! Reading of boolean networks with up to 64 variables in the `.bnet` format.
+//! See [crate::u32::bnet] for the description of the format.
+
+use crate::u64::bn::{BooleanNetwork, BooleanNetworkBuilder};
+use crate::u32::bnet::{ParseError, parse_bnet_builder};
+
+/// Read a boolean network from a string in the `.bnet` format.
+pub fn parse_bnet(input: &str) -> Result<BooleanNetwork, ParseError> {
+    return parse_bnet_builder(input, 64)
+        .map(|builder| BooleanNetworkBuilder::from_builder(builder).build_network());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bnet_parse_wide_network() {
+        let mut input = String::from("targets, factors\n");
+        for i in 0..40 {
+            input.push_str(&format!("v{}, !v{}\n", i, (i + 1) % 40));
+        }
+        let network = parse_bnet(&input).unwrap();
+        assert_eq!(40, network.var_count());
+        assert!(crate::u32::bnet::parse_bnet(&input).is_err());
+        input.push_str("v40, v39 & v40\n");
+        assert_eq!(41, parse_bnet(&input).unwrap().var_count());
+    }
+
+}
@@ -0,0 +1,7 @@
+use crate::u64::bn::StateId;
+use crate::u32::decomposition::ReachableSccDecomposition;
+
+/// SCC decomposition of the part of the state space of a [crate::u64::bn::BooleanNetwork]
+/// which has been explored by an algorithm (typically the states reachable from some initial
+/// states), see [ReachableSccDecomposition].
+pub type SccDecomposition = ReachableSccDecomposition<StateId>;
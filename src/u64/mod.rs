@@ -0,0 +1,11 @@
+//! Defines SCC decomposition for boolean networks of up-to 64 variables.
+//!
+//! The full state space of such networks typically does not fit into memory, hence the
+//! algorithms in this module only explore states reachable from the given initial states
+//! and store them in hash-based structures. Update functions and variables are shared
+//! with the [crate::u32] module.
+
+pub mod bn;
+pub mod bnet;
+pub mod decomposition;
+pub mod sequential;
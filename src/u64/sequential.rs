@@ -0,0 +1,86 @@
+use crate::u64::bn::{StateId, BooleanNetwork};
+use crate::u64::decomposition::SccDecomposition;
+use crate::u32::sequential::{scc_reachable, find_reachable_attractors};
+
+/// Attractor of a network with up to 64 variables, see [crate::u32::sequential::Attractor].
+pub type Attractor = crate::u32::sequential::Attractor<StateId>;
+
+/// Compute the SCC decomposition of the part of the [network] state space which is reachable
+/// from the given [initial_states], see [scc_reachable].
+pub fn scc(network: &BooleanNetwork, initial_states: &[StateId]) -> SccDecomposition {
+    return scc_reachable(network, initial_states);
+}
+
+/// Compute all attractors of the [network] reachable from the given [initial_states].
+pub fn attractors(network: &BooleanNetwork, initial_states: &[StateId]) -> Vec<Attractor> {
+    return find_attractors(network, &scc(network, initial_states));
+}
+
+/// Identify attractors in an already computed [decomposition], see [find_reachable_attractors].
+pub fn find_attractors(network: &BooleanNetwork, decomposition: &SccDecomposition) -> Vec<Attractor> {
+    return find_reachable_attractors(network, decomposition);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u32::models::*;
+    use crate::u64::bn::BooleanNetworkBuilder;
+    use crate::u32::expression::var;
+
+    #[test]
+    fn scc_matches_u32_scc() {
+        for network in &[demo_model_disorder(), demo_model_oscillation(), demo_model_bistable(), demo_model_stable()] {
+            let wide = BooleanNetwork::from(network);
+            // Starting from all states, the whole state space is explored.
+            let initial: Vec<StateId> = network.states().map(|s| StateId { value: s.value as u64 }).collect();
+            let expected = crate::u32::sequential::scc(network);
+            let decomposition = scc(&wide, &initial);
+            assert_eq!(expected.component_count(), decomposition.component_count());
+            for s in network.states() {
+                let wide_s = StateId { value: s.value as u64 };
+                assert_eq!(Some(expected.component_of(&s)), decomposition.component_of(&wide_s));
+            }
+        }
+    }
+
+    #[test]
+    fn attractors_match_u32_attractors() {
+        let network = demo_model_oscillation();
+        let wide = BooleanNetwork::from(&network);
+        let expected: Vec<Vec<u64>> = crate::u32::sequential::attractors(&network).iter()
+            .map(|a| a.states().iter().map(|s| s.value as u64).collect())
+            .collect();
+        let initial: Vec<StateId> = network.states().map(|s| StateId { value: s.value as u64 }).collect();
+        let actual: Vec<Vec<u64>> = attractors(&wide, &initial).iter()
+            .map(|a| a.states().iter().map(|s| s.value).collect())
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn scc_wide_network_reachable_part() {
+        // A ring of 48 variables where every variable copies its predecessor and the first one
+        // negates the last one. From the zero state, this is a single cycle of 96 states
+        // (a "wave" of ones followed by a "wave" of zeros), since there is always exactly
+        // one variable which can change.
+        let mut builder = BooleanNetworkBuilder::new();
+        let vars: Vec<_> = (0..48).map(|i| builder.make_variable(&format!("v{}", i))).collect();
+        builder.update_function(&vars[0], !var(vars[47]));
+        for i in 1..48 {
+            builder.update_function(&vars[i], var(vars[i - 1]));
+        }
+        let network = builder.build_network();
+        let decomposition = scc(&network, &[StateId { value: 0 }]);
+        assert!(decomposition.contains(&StateId { value: 0 }));
+        assert!(!decomposition.contains(&StateId { value: 0b101 }));
+        assert_eq!(96, decomposition.state_count());
+        assert_eq!(1, decomposition.component_count());
+        let attractors = find_attractors(&network, &decomposition);
+        assert_eq!(1, attractors.len());
+        let cycle = attractors[0].states();
+        assert!(cycle.contains(&StateId { value: 0 }));
+        assert!(cycle.contains(&StateId { value: (1 << 48) - 1 }));
+    }
+
+}